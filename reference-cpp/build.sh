@@ -1,3 +1,41 @@
 cd "$(dirname "$0")"
 mkdir -p build
-clang++ -std=c++17 -stdlib=libstdc++ main.cpp -o build/main -O3 -march=native
+CXX=${CXX:-clang++}
+FLAGS="-std=c++17 -O3 -march=native"
+case "$CXX" in
+  *clang*) FLAGS="$FLAGS -stdlib=libstdc++" ;;
+esac
+
+# The parallel reference implementations use oneTBB, OpenMP and the parallel algorithms of the standard library.
+# These are optional: implementations of which the library is not found are not available.
+supports() {
+  printf '%s\n' "$1" | $CXX $FLAGS -x c++ - -o /dev/null $2 2>/dev/null
+}
+
+if [ -d oneTBB-install ]; then
+  TBB="-IoneTBB-install/include -LoneTBB-install/lib -ltbb"
+else
+  TBB="-ltbb"
+fi
+if supports '#include <tbb/parallel_scan.h>
+int main() { return 0; }' "$TBB"; then
+  FLAGS="$FLAGS -DHAS_TBB $TBB"
+  # The parallel algorithms of libstdc++ use TBB as backend
+  if supports '#include <execution>
+#include <numeric>
+int main() { int a[1] = { 0 }; std::inclusive_scan(std::execution::par, a, a + 1, a); return 0; }' ""; then
+    FLAGS="$FLAGS -DHAS_STD_PAR"
+  else
+    echo "The parallel algorithms of the standard library are not available, std::execution::par is skipped."
+  fi
+else
+  echo "oneTBB was not found, the TBB and std::execution::par references are skipped."
+fi
+if supports '#include <omp.h>
+int main() { return omp_get_max_threads(); }' "-fopenmp"; then
+  FLAGS="$FLAGS -fopenmp"
+else
+  echo "OpenMP is not supported by $CXX, the OpenMP reference is skipped."
+fi
+
+$CXX main.cpp -o build/main $FLAGS
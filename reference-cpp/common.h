@@ -40,3 +40,21 @@ void run(P prepare, F f) {
   auto msec = std::chrono::duration_cast<std::chrono::microseconds>(total);
   printf("%ld\n", msec.count() / RUNS);
 }
+
+// Like run, but prints the time of every run in nanoseconds, separated by spaces.
+// Used for the parallel implementations, such that the benchmark program can compute statistics.
+template<class P, class F>
+void run_samples(P prepare, F f) {
+  // Warm-up run
+  prepare();
+  f();
+
+  for (int j = 0; j < RUNS; j++) {
+    prepare();
+    auto before = std::chrono::steady_clock::now();
+    f();
+    auto t = std::chrono::steady_clock::now() - before;
+    printf(j == 0 ? "%ld" : " %ld", (long) std::chrono::duration_cast<std::chrono::nanoseconds>(t).count());
+  }
+  printf("\n");
+}
@@ -1,7 +1,26 @@
+#include <cstdlib>
 #include <cstring>
 #include <string>
+#include <thread>
 #include "common.h"
 
+// The parallel libraries are optional, build.sh defines HAS_TBB and HAS_STD_PAR if they are available
+// and enables OpenMP if the compiler supports it.
+#ifdef HAS_TBB
+#include <tbb/blocked_range.h>
+#include <tbb/global_control.h>
+#include <tbb/parallel_for.h>
+#include <tbb/parallel_scan.h>
+#endif
+#ifdef _OPENMP
+#include <omp.h>
+#endif
+#ifdef HAS_STD_PAR
+#include <algorithm>
+#include <execution>
+#include <numeric>
+#endif
+
 void __attribute__ ((noinline)) test_sequential_scan(int size, uint64_t* input, uint64_t* output) {
   int accum = 0;
   for (int i = 0; i < size; i++) {
@@ -36,8 +55,172 @@ void __attribute__ ((noinline)) test_sequential_compact(uint64_t mask, int size,
   }
 }
 
+// Sequential scan of the rows in [row_start, row_end)
+void scan_rows(int row_start, int row_end, int row_length, uint64_t* input, uint64_t* output) {
+  for (int i = row_start; i < row_end; i++) {
+    uint64_t accum = 0;
+    for (int j = i * row_length; j < (i + 1) * row_length; j++) {
+      accum += input[j];
+      output[j] = accum;
+    }
+  }
+}
+
+// The multidimensional parallel scans divide the rows over the threads if there are at least as many rows as threads.
+// Otherwise they scan the rows one by one, with a parallel scan per row.
+
+#ifdef HAS_TBB
+void __attribute__ ((noinline)) test_tbb_scan(int size, uint64_t* input, uint64_t* output) {
+  tbb::parallel_scan(
+    tbb::blocked_range<int>(0, size),
+    (uint64_t) 0,
+    [&] (const tbb::blocked_range<int>& range, uint64_t accum, bool is_final) {
+      for (int i = range.begin(); i < range.end(); i++) {
+        accum += input[i];
+        if (is_final) {
+          output[i] = accum;
+        }
+      }
+      return accum;
+    },
+    [] (uint64_t left, uint64_t right) { return left + right; }
+  );
+}
+
+void __attribute__ ((noinline)) test_multidim_tbb_scan(int row_length, int row_count, int thread_count, uint64_t* input, uint64_t* output) {
+  if (row_count >= thread_count) {
+    tbb::parallel_for(tbb::blocked_range<int>(0, row_count), [&] (const tbb::blocked_range<int>& range) {
+      scan_rows(range.begin(), range.end(), row_length, input, output);
+    });
+  } else {
+    for (int i = 0; i < row_count; i++) {
+      test_tbb_scan(row_length, input + i * row_length, output + i * row_length);
+    }
+  }
+}
+#endif
+
+#ifdef _OPENMP
+void __attribute__ ((noinline)) test_openmp_scan(int size, uint64_t* input, uint64_t* output) {
+  uint64_t accum = 0;
+  #pragma omp parallel for reduction(inscan, +: accum)
+  for (int i = 0; i < size; i++) {
+    accum += input[i];
+    #pragma omp scan inclusive(accum)
+    output[i] = accum;
+  }
+}
+
+void __attribute__ ((noinline)) test_multidim_openmp_scan(int row_length, int row_count, int thread_count, uint64_t* input, uint64_t* output) {
+  if (row_count >= thread_count) {
+    #pragma omp parallel for
+    for (int i = 0; i < row_count; i++) {
+      scan_rows(i, i + 1, row_length, input, output);
+    }
+  } else {
+    for (int i = 0; i < row_count; i++) {
+      test_openmp_scan(row_length, input + i * row_length, output + i * row_length);
+    }
+  }
+}
+#endif
+
+#ifdef HAS_STD_PAR
+void __attribute__ ((noinline)) test_std_par_scan(int size, uint64_t* input, uint64_t* output) {
+  std::inclusive_scan(std::execution::par, input, input + size, output);
+}
+
+void __attribute__ ((noinline)) test_multidim_std_par_scan(int row_length, int row_count, int thread_count, int* rows, uint64_t* input, uint64_t* output) {
+  if (row_count >= thread_count) {
+    std::for_each(std::execution::par, rows, rows + row_count, [&] (int i) {
+      scan_rows(i, i + 1, row_length, input, output);
+    });
+  } else {
+    for (int i = 0; i < row_count; i++) {
+      test_std_par_scan(row_length, input + i * row_length, output + i * row_length);
+    }
+  }
+}
+#endif
+
+bool ends_with(const std::string& value, const std::string& suffix) {
+  return value.size() >= suffix.size() && value.compare(value.size() - suffix.size(), suffix.size(), suffix) == 0;
+}
+
+// Runs a parallel scan of a library: test_case is one of the sequential scan cases, with 'sequential' replaced
+// by 'tbb', 'openmp' or 'std-par'. Returns false if the test case is unknown, and exits with status 1 if the
+// library is not available.
+bool run_parallel(const std::string& test_case, int size, int row_length, int row_count, int thread_count, uint64_t* input, uint64_t* output) {
+  std::string library;
+  for (std::string candidate : { "tbb", "openmp", "std-par" }) {
+    if (ends_with(test_case, "-" + candidate)) {
+      library = candidate;
+    }
+  }
+  if (library.empty()) {
+    return false;
+  }
+  std::string scan = test_case.substr(0, test_case.size() - library.size() - 1);
+  bool inplace = scan == "scan-inplace" || scan == "scan-inplace-multidim";
+  bool multidim = scan == "scan-multidim" || scan == "scan-inplace-multidim";
+  if (!inplace && !multidim && scan != "scan") {
+    return false;
+  }
+  uint64_t* destination = inplace ? input : output;
+  auto prepare = [&] () {
+    if (inplace) {
+      fill(size, input);
+    }
+  };
+
+#ifdef HAS_TBB
+  // Limits the number of threads of TBB, and of the TBB backend of the parallel algorithms of the standard library
+  tbb::global_control control(tbb::global_control::max_allowed_parallelism, thread_count);
+#endif
+#ifdef _OPENMP
+  omp_set_num_threads(thread_count);
+#endif
+
+  if (library == "tbb") {
+#ifdef HAS_TBB
+    if (multidim) {
+      run_samples(prepare, [&] () { test_multidim_tbb_scan(row_length, row_count, thread_count, input, destination); });
+    } else {
+      run_samples(prepare, [&] () { test_tbb_scan(size, input, destination); });
+    }
+    return true;
+#endif
+  } else if (library == "openmp") {
+#ifdef _OPENMP
+    if (multidim) {
+      run_samples(prepare, [&] () { test_multidim_openmp_scan(row_length, row_count, thread_count, input, destination); });
+    } else {
+      run_samples(prepare, [&] () { test_openmp_scan(size, input, destination); });
+    }
+    return true;
+#endif
+  } else if (library == "std-par") {
+#ifdef HAS_STD_PAR
+    int* rows = new int[row_count];
+    for (int i = 0; i < row_count; i++) {
+      rows[i] = i;
+    }
+    if (multidim) {
+      run_samples(prepare, [&] () { test_multidim_std_par_scan(row_length, row_count, thread_count, rows, input, destination); });
+    } else {
+      run_samples(prepare, [&] () { test_std_par_scan(size, input, destination); });
+    }
+    delete[] rows;
+    return true;
+#endif
+  }
+
+  printf("Not available: the reference was built without %s.\n", library.c_str());
+  exit(1);
+}
+
 int main(int argc, char *argv[]) {
-  if (argc < 3) {
+  if (argc < 5) {
     printf("Usage: ./main test-case input-size row-length row-count (thread-count)\n");
     return 0;
   }
@@ -66,6 +249,13 @@ int main(int argc, char *argv[]) {
     return 0;
   }
 
+  // Parse the thread count of the parallel implementations
+  int thread_count = argc > 5 ? std::stoi(argv[5]) : (int) std::thread::hardware_concurrency();
+  if (thread_count <= 0) {
+    printf("thread-count should be positive.\n");
+    return 0;
+  }
+
   // Allocate input and output arrays
   uint64_t* input = new uint64_t[size];
   uint64_t* output = new uint64_t[size];
@@ -103,7 +293,7 @@ int main(int argc, char *argv[]) {
       [&] () { test_sequential_compact(mask, size, input, output); }
     );
 
-  } else {
+  } else if (!run_parallel(argv[1], size, row_length, row_count, thread_count, input, output)) {
     printf("Unknown test case.\n");
   }
 
@@ -1 +1,23 @@
-pub mod scan;
\ No newline at end of file
+use crate::utils::benchmark::Settings;
+
+pub mod compact;
+pub mod partition;
+pub mod radix_sort;
+pub mod recurrence;
+pub mod reduce;
+pub mod rle;
+pub mod scan;
+pub mod stream;
+
+// Verification mode of all cases: compares the full outputs of the parallel implementations against
+// the sequential references (see scan::verify). Returns false if any implementation produced a wrong result.
+pub fn verify(settings: &Settings, shapes: &[Vec<usize>]) -> bool {
+  let mut correct = scan::verify(settings, shapes);
+  correct &= compact::verify(settings, shapes);
+  correct &= partition::verify(settings, shapes);
+  correct &= radix_sort::verify(settings, shapes);
+  correct &= rle::verify(settings, shapes);
+  correct &= recurrence::verify(settings, shapes);
+  correct &= reduce::verify(settings, shapes);
+  correct
+}
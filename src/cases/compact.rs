@@ -0,0 +1,172 @@
+use core::sync::atomic::{Ordering, AtomicU64};
+use num_format::{Locale, ToFormattedString};
+use crate::cases::scan::{self, fill, row_column_chained};
+use crate::core::worker::*;
+use crate::utils;
+use crate::utils::array::Multidimensional;
+use crate::utils::benchmark::{benchmark, ChartStyle, Settings};
+
+mod chained;
+
+// Names of all parallel implementations, as used in the benchmarks
+pub const ALGORITHMS: [&str; 1] = ["Chained compact"];
+
+// Ratios of the benchmarks: one in 'ratio' elements is selected, matching the compact-2 and compact-8 cases of the C++ reference
+pub const RATIOS: [u64; 2] = [2, 8];
+
+// Bytes moved per element: every element is read, and one in 'ratio' elements is written
+fn bytes_per_element(ratio: u64) -> f64 {
+  8.0 + 8.0 / ratio as f64
+}
+
+pub fn run_compact(settings: &Settings, sizes: &[usize]) { // One-dimensional stream compaction
+  for &size in sizes {
+    let input = unsafe { utils::array::MultArray::new([size]) };
+    let output = unsafe { utils::array::MultArray::new([size]) };
+    let counts = [AtomicU64::new(0)];
+    let temp = row_column_chained::create_temp(&input);
+    fill(input.get_data());
+
+    for ratio in RATIOS {
+      let mask = ratio - 1;
+      let name = format!("Compact 1/{} (n = {})", ratio, size.to_formatted_string(&Locale::en));
+      benchmark(
+          settings,
+          ChartStyle::WithKey,
+          &name,
+          &[size],
+          bytes_per_element(ratio),
+          || {},
+          || { reference_sequential(mask, &input, &output, &counts) }
+        )
+        .parallel("Chained compact", 7, None, true, || {}, |thread_count| {
+          let task = chained::init_single(mask, &input, &temp, &output, &counts);
+          Workers::run(thread_count, task);
+          compute_output(output.get_data(), &counts, size)
+        })
+        .cpp_sequential("Reference sequential C++", &format!("compact-{}-sequential", ratio), size, size, 1);
+    }
+  }
+}
+
+pub fn run_compact_multidim(settings: &Settings, shapes: &[Vec<usize>]) { // Stream compaction of every innermost row
+  for shape in shapes {
+    let input = unsafe { utils::array::DynMultArray::new(shape) };
+    let output = unsafe { utils::array::DynMultArray::new(shape) };
+    let counts: Box<[AtomicU64]> = (0 .. input.total_inner_count()).map(|_| AtomicU64::new(0)).collect();
+    let temp = row_column_chained::create_temp(&input);
+    fill(input.get_data());
+
+    for ratio in RATIOS {
+      let mask = ratio - 1;
+      let name = format!("Compact 1/{} (sh = {:?})", ratio, shape);
+      benchmark(
+          settings,
+          ChartStyle::WithKey,
+          &name,
+          shape,
+          bytes_per_element(ratio),
+          || {},
+          || { reference_sequential(mask, &input, &output, &counts) }
+        )
+        .parallel("Chained compact", 7, None, true, || {}, |thread_count| {
+          let task = chained::init_single(mask, &input, &temp, &output, &counts);
+          Workers::run(thread_count, task);
+          compute_output(output.get_data(), &counts, input.get_inner_size())
+        });
+    }
+  }
+}
+
+// Verification of the compaction, see scan::verify. Elements after the selected elements of a row are not written,
+// and thus keep the value of scan::clear in both the reference and the parallel output.
+pub fn verify(settings: &Settings, shapes: &[Vec<usize>]) -> bool {
+  let mut correct = true;
+  for shape in shapes {
+    let input = unsafe { utils::array::DynMultArray::new(shape) };
+    let output = unsafe { utils::array::DynMultArray::new(shape) };
+    let counts: Box<[AtomicU64]> = (0 .. input.total_inner_count()).map(|_| AtomicU64::new(0)).collect();
+    let temp = row_column_chained::create_temp(&input);
+    fill(input.get_data());
+
+    for ratio in RATIOS {
+      let mask = ratio - 1;
+      scan::clear(output.get_data());
+      reference_sequential(mask, &input, &output, &counts);
+      let expected_output = scan::snapshot(output.get_data());
+      let expected_counts = scan::snapshot(&counts);
+
+      println!("Verifying compact 1/{} of shape {:?}", ratio, shape);
+      for name in ALGORITHMS {
+        if !settings.includes_algorithm(name) {
+          continue;
+        }
+        for &thread_count in &settings.thread_counts {
+          correct &= scan::verify_case(name, thread_count, "out-of-place", || {
+            scan::clear(output.get_data());
+            scan::clear(&counts);
+            Workers::run(thread_count, chained::init_single(mask, &input, &temp, &output, &counts));
+            scan::find_mismatch("counts", &expected_counts, &counts, 1)
+              .or_else(|| scan::find_mismatch("output", &expected_output, output.get_data(), input.get_inner_size()))
+          });
+        }
+      }
+    }
+  }
+  correct
+}
+
+// Same predicate as the C++ reference, selecting approximately one in mask + 1 elements
+#[inline(always)]
+pub fn predicate(mask: u64, mut value: u64) -> bool {
+  value ^= value >> 11;
+  value ^= value << 7;
+  value ^= value >> 5;
+  (value & mask) == mask
+}
+
+// Writes the selected elements of 'input' to the start of 'output', and returns the number of selected elements
+pub fn compact_sequential(mask: u64, input: &[AtomicU64], output: &[AtomicU64]) -> u64 {
+  let mut output_index = 0;
+  for value in input {
+    let value = value.load(Ordering::Relaxed);
+    if predicate(mask, value) {
+      output[output_index].store(value, Ordering::Relaxed);
+      output_index += 1;
+    }
+  }
+  output_index as u64
+}
+
+pub fn count_sequential(mask: u64, input: &[AtomicU64]) -> u64 {
+  let mut count = 0;
+  for value in input {
+    if predicate(mask, value.load(Ordering::Relaxed)) {
+      count += 1;
+    }
+  }
+  count
+}
+
+pub fn reference_sequential<A: Multidimensional<AtomicU64>>(mask: u64, input: &A, output: &A, counts: &[AtomicU64]) -> u64 {
+  let row_length = input.get_inner_size();
+  for (row, count) in counts.iter().enumerate() {
+    let range = row * row_length .. (row + 1) * row_length;
+    count.store(compact_sequential(mask, &input.get_data()[range.clone()], &output.get_data()[range]), Ordering::Relaxed);
+  }
+  compute_output(output.get_data(), counts, row_length)
+}
+
+// Checksum of the number of selected elements and the first and last selected element of every row.
+// Elements after the selected elements of a row are not written, and thus not included.
+pub fn compute_output(output: &[AtomicU64], counts: &[AtomicU64], row_length: usize) -> u64 {
+  let mut checksum = 0;
+  for (row, count) in counts.iter().enumerate() {
+    let count = count.load(Ordering::Relaxed) as usize;
+    checksum += count as u64;
+    if count > 0 {
+      checksum += output[row * row_length].load(Ordering::Relaxed) + output[row * row_length + count - 1].load(Ordering::Relaxed);
+    }
+  }
+  checksum
+}
@@ -0,0 +1,71 @@
+use core::sync::atomic::{Ordering, AtomicU64};
+use crate::cases::compact::{compact_sequential, count_sequential};
+use crate::cases::scan::BLOCK_SIZE;
+use crate::cases::scan::chained_count::chained_count;
+use crate::cases::scan::row_column_chained::{ BlockInfo, reset };
+use crate::core::worker::*;
+use crate::core::task::*;
+use crate::core::workassisting_loop::*;
+use crate::utils::array::Multidimensional;
+
+// Stream compaction of every innermost row, using the chained scan over the number of selected elements per block.
+// The selected elements of a row are written to the start of the same row in the output,
+// and the number of selected elements of every row is stored in 'counts'.
+// The prefix of a block is the number of selected elements in the row up to and including that block.
+
+struct Data<'a> {
+  mask: u64,
+  input: &'a [AtomicU64],
+  temp: &'a [BlockInfo],
+  output: &'a [AtomicU64],
+  counts: &'a [AtomicU64],
+  blocks_per_row: u64,
+  inner_size: u64
+}
+
+pub fn init_single<A: Multidimensional<AtomicU64>>(mask: u64, input: &A, temp: &[BlockInfo], output: &A, counts: &[AtomicU64]) -> Task {
+  reset(temp);
+  create_task(mask, input, temp, output, counts)
+}
+
+fn create_task<A: Multidimensional<AtomicU64>>(mask: u64, input_m: &A, temp: &[BlockInfo], output_m: &A, counts: &[AtomicU64]) -> Task {
+  let inner_size = input_m.get_inner_size() as u64;
+  let inner_rows = input_m.total_inner_count() as u64;
+  let input = input_m.get_data();
+  let output = output_m.get_data();
+  assert_eq!(counts.len(), inner_rows as usize);
+
+  let blocks_per_row = inner_size.div_ceil(BLOCK_SIZE);
+  let block_count = blocks_per_row.checked_mul(inner_rows).expect("Block count overflowed u64 size") as u32;
+
+  Task::new_dataparallel::<Data>(run, finish, Data{ mask, input, temp, output, counts, blocks_per_row, inner_size }, block_count, false)
+}
+
+fn run(_workers: &Workers, task: *const TaskObject<Data>, loop_arguments: LoopArguments) {
+  let data = unsafe { TaskObject::get_data(task) };
+  let mut sequential = true;
+
+  workassisting_loop!(loop_arguments, |block_index| {
+    let row_idx = block_index as usize / data.blocks_per_row as usize;
+    let column_idx = block_index as usize - (row_idx * data.blocks_per_row as usize);
+
+    let row_start = row_idx * data.inner_size as usize;
+    let row_end = row_start + data.inner_size as usize;
+    let start = row_start + column_idx * BLOCK_SIZE as usize;
+    let end = (start + BLOCK_SIZE as usize).min(row_end);
+
+    let prefix = chained_count(data.temp, block_index as usize, column_idx, &mut sequential,
+      || count_sequential(data.mask, &data.input[start .. end]),
+      |offset| offset + compact_sequential(data.mask, &data.input[start .. end], &data.output[row_start + offset as usize .. row_end])
+    );
+
+    if column_idx == data.blocks_per_row as usize - 1 {
+      data.counts[row_idx].store(prefix, Ordering::Relaxed);
+    }
+  });
+}
+
+fn finish(workers: &Workers, task: *mut TaskObject<Data>) {
+  let _ = unsafe { TaskObject::take_data(task) };
+  workers.finish();
+}
@@ -0,0 +1,171 @@
+use core::sync::atomic::{Ordering, AtomicU64};
+use num_format::{Locale, ToFormattedString};
+use crate::cases::compact::predicate;
+use crate::cases::scan::{self, fill, row_column_chained};
+use crate::core::worker::*;
+use crate::utils;
+use crate::utils::array::Multidimensional;
+use crate::utils::benchmark::{benchmark, ChartStyle, Settings};
+
+mod chained;
+
+// Names of all parallel implementations, as used in the benchmarks
+pub const ALGORITHMS: [&str; 1] = ["Chained partition"];
+
+// Every element is read once and written to one of the two sides
+const BYTES_PER_ELEMENT: f64 = 16.0;
+
+// Selects approximately half of the elements, with the same predicate as the compaction benchmarks
+fn select_half(value: u64) -> bool {
+  predicate(1, value)
+}
+
+pub fn run_partition(settings: &Settings, sizes: &[usize]) { // One-dimensional stable partition
+  for &size in sizes {
+    let input = unsafe { utils::array::MultArray::new([size]) };
+    let selected = unsafe { utils::array::MultArray::new([size]) };
+    let rejected = unsafe { utils::array::MultArray::new([size]) };
+    let counts = [AtomicU64::new(0)];
+    let temp = row_column_chained::create_temp(&input);
+    fill(input.get_data());
+
+    let name = "Partition (n = ".to_owned() + &size.to_formatted_string(&Locale::en) + ")";
+    benchmark(
+        settings,
+        ChartStyle::WithKey,
+        &name,
+        &[size],
+        BYTES_PER_ELEMENT,
+        || {},
+        || { reference_sequential(select_half, &input, &selected, &rejected, &counts) }
+      )
+      .parallel("Chained partition", 7, None, true, || {}, |thread_count| {
+        let task = chained::init_slice(select_half, input.get_data(), &temp, selected.get_data(), rejected.get_data(), &counts);
+        Workers::run(thread_count, task);
+        compute_output(selected.get_data(), rejected.get_data(), &counts, size)
+      });
+  }
+}
+
+pub fn run_partition_multidim(settings: &Settings, shapes: &[Vec<usize>]) { // Stable partition of every innermost row
+  for shape in shapes {
+    let input = unsafe { utils::array::DynMultArray::new(shape) };
+    let selected = unsafe { utils::array::DynMultArray::new(shape) };
+    let rejected = unsafe { utils::array::DynMultArray::new(shape) };
+    let counts: Box<[AtomicU64]> = (0 .. input.total_inner_count()).map(|_| AtomicU64::new(0)).collect();
+    let temp = row_column_chained::create_temp(&input);
+    fill(input.get_data());
+
+    let name = "Partition (sh = ".to_owned() + &format!("{:?}", shape) + ")";
+    benchmark(
+        settings,
+        ChartStyle::WithKey,
+        &name,
+        shape,
+        BYTES_PER_ELEMENT,
+        || {},
+        || { reference_sequential(select_half, &input, &selected, &rejected, &counts) }
+      )
+      .parallel("Chained partition", 7, None, true, || {}, |thread_count| {
+        let task = chained::init_single(select_half, &input, &temp, &selected, &rejected, &counts);
+        Workers::run(thread_count, task);
+        compute_output(selected.get_data(), rejected.get_data(), &counts, input.get_inner_size())
+      });
+  }
+}
+
+// Verification of the partition, see scan::verify. Both sides are compared in full, hence elements after
+// the selected or rejected elements of a row must keep the value of scan::clear.
+pub fn verify(settings: &Settings, shapes: &[Vec<usize>]) -> bool {
+  let mut correct = true;
+  for shape in shapes {
+    let input = unsafe { utils::array::DynMultArray::new(shape) };
+    let selected = unsafe { utils::array::DynMultArray::new(shape) };
+    let rejected = unsafe { utils::array::DynMultArray::new(shape) };
+    let counts: Box<[AtomicU64]> = (0 .. input.total_inner_count()).map(|_| AtomicU64::new(0)).collect();
+    let temp = row_column_chained::create_temp(&input);
+    fill(input.get_data());
+
+    scan::clear(selected.get_data());
+    scan::clear(rejected.get_data());
+    reference_sequential(select_half, &input, &selected, &rejected, &counts);
+    let expected_selected = scan::snapshot(selected.get_data());
+    let expected_rejected = scan::snapshot(rejected.get_data());
+    let expected_counts = scan::snapshot(&counts);
+
+    println!("Verifying partition of shape {:?}", shape);
+    for name in ALGORITHMS {
+      if !settings.includes_algorithm(name) {
+        continue;
+      }
+      for &thread_count in &settings.thread_counts {
+        correct &= scan::verify_case(name, thread_count, "out-of-place", || {
+          scan::clear(selected.get_data());
+          scan::clear(rejected.get_data());
+          scan::clear(&counts);
+          Workers::run(thread_count, chained::init_single(select_half, &input, &temp, &selected, &rejected, &counts));
+          scan::find_mismatch("counts", &expected_counts, &counts, 1)
+            .or_else(|| scan::find_mismatch("selected", &expected_selected, selected.get_data(), input.get_inner_size()))
+            .or_else(|| scan::find_mismatch("rejected", &expected_rejected, rejected.get_data(), input.get_inner_size()))
+        });
+      }
+    }
+  }
+  correct
+}
+
+// Writes the selected elements of 'input' to the start of 'selected' and the other elements to the start of 'rejected'.
+// Returns the number of selected elements.
+pub fn partition_sequential(predicate: fn(u64) -> bool, input: &[AtomicU64], selected: &[AtomicU64], rejected: &[AtomicU64]) -> u64 {
+  let mut selected_index = 0;
+  let mut rejected_index = 0;
+  for value in input {
+    let value = value.load(Ordering::Relaxed);
+    if predicate(value) {
+      selected[selected_index].store(value, Ordering::Relaxed);
+      selected_index += 1;
+    } else {
+      rejected[rejected_index].store(value, Ordering::Relaxed);
+      rejected_index += 1;
+    }
+  }
+  selected_index as u64
+}
+
+pub fn count_selected(predicate: fn(u64) -> bool, input: &[AtomicU64]) -> u64 {
+  let mut count = 0;
+  for value in input {
+    if predicate(value.load(Ordering::Relaxed)) {
+      count += 1;
+    }
+  }
+  count
+}
+
+pub fn reference_sequential<A: Multidimensional<AtomicU64>>(predicate: fn(u64) -> bool, input: &A, selected: &A, rejected: &A, counts: &[AtomicU64]) -> u64 {
+  let row_length = input.get_inner_size();
+  for (row, count) in counts.iter().enumerate() {
+    let range = row * row_length .. (row + 1) * row_length;
+    let selected_count = partition_sequential(predicate, &input.get_data()[range.clone()], &selected.get_data()[range.clone()], &rejected.get_data()[range]);
+    count.store(selected_count, Ordering::Relaxed);
+  }
+  compute_output(selected.get_data(), rejected.get_data(), counts, row_length)
+}
+
+// Checksum of the number of selected elements and the first and last element of both sides of every row
+pub fn compute_output(selected: &[AtomicU64], rejected: &[AtomicU64], counts: &[AtomicU64], row_length: usize) -> u64 {
+  let mut checksum = 0;
+  for (row, count) in counts.iter().enumerate() {
+    let selected_count = count.load(Ordering::Relaxed) as usize;
+    let rejected_count = row_length - selected_count;
+    let row_start = row * row_length;
+    checksum += selected_count as u64;
+    if selected_count > 0 {
+      checksum += selected[row_start].load(Ordering::Relaxed) + selected[row_start + selected_count - 1].load(Ordering::Relaxed);
+    }
+    if rejected_count > 0 {
+      checksum += 3 * (rejected[row_start].load(Ordering::Relaxed) + rejected[row_start + rejected_count - 1].load(Ordering::Relaxed));
+    }
+  }
+  checksum
+}
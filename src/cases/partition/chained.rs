@@ -0,0 +1,87 @@
+use core::sync::atomic::{Ordering, AtomicU64};
+use crate::cases::partition::{count_selected, partition_sequential};
+use crate::cases::scan::BLOCK_SIZE;
+use crate::cases::scan::chained_count::chained_count;
+use crate::cases::scan::row_column_chained::{ BlockInfo, reset };
+use crate::core::worker::*;
+use crate::core::task::*;
+use crate::core::workassisting_loop::*;
+use crate::utils::array::Multidimensional;
+
+// Stable partition of every innermost row in a single pass, using the chained scan over the number of selected
+// elements per block. The selected elements of a row are written to the start of the row in 'selected',
+// the other elements to the start of the row in 'rejected', both in their original order.
+// The look-back gives both offsets of a block at once: the prefix of the previous block is the number
+// of selected elements before the block, and the remaining elements before the block were rejected.
+// The number of selected elements of every row is stored in 'counts'.
+
+struct Data<'a> {
+  predicate: fn(u64) -> bool,
+  input: &'a [AtomicU64],
+  temp: &'a [BlockInfo],
+  selected: &'a [AtomicU64],
+  rejected: &'a [AtomicU64],
+  counts: &'a [AtomicU64],
+  blocks_per_row: u64,
+  inner_size: u64
+}
+
+pub fn init_single<A: Multidimensional<AtomicU64>>(predicate: fn(u64) -> bool, input: &A, temp: &[BlockInfo], selected: &A, rejected: &A, counts: &[AtomicU64]) -> Task {
+  reset(temp);
+  create_task(predicate, input.get_data(), input.get_inner_size(), temp, selected.get_data(), rejected.get_data(), counts)
+}
+
+// Partitions a flat slice as a single row. 'counts' contains one element.
+pub fn init_slice(predicate: fn(u64) -> bool, input: &[AtomicU64], temp: &[BlockInfo], selected: &[AtomicU64], rejected: &[AtomicU64], counts: &[AtomicU64]) -> Task {
+  reset(temp);
+  create_task(predicate, input, input.len(), temp, selected, rejected, counts)
+}
+
+fn create_task<'a>(predicate: fn(u64) -> bool, input: &'a [AtomicU64], inner_size: usize, temp: &'a [BlockInfo], selected: &'a [AtomicU64], rejected: &'a [AtomicU64], counts: &'a [AtomicU64]) -> Task {
+  let inner_size = inner_size as u64;
+  let inner_rows = (input.len() as u64).checked_div(inner_size).unwrap_or(0);
+  assert_eq!(selected.len(), input.len());
+  assert_eq!(rejected.len(), input.len());
+  assert_eq!(counts.len(), inner_rows as usize);
+
+  let blocks_per_row = inner_size.div_ceil(BLOCK_SIZE);
+  let block_count = blocks_per_row.checked_mul(inner_rows).expect("Block count overflowed u64 size") as u32;
+
+  Task::new_dataparallel::<Data>(run, finish, Data{ predicate, input, temp, selected, rejected, counts, blocks_per_row, inner_size }, block_count, false)
+}
+
+fn run(_workers: &Workers, task: *const TaskObject<Data>, loop_arguments: LoopArguments) {
+  let data = unsafe { TaskObject::get_data(task) };
+  let mut sequential = true;
+
+  workassisting_loop!(loop_arguments, |block_index| {
+    let row_idx = block_index as usize / data.blocks_per_row as usize;
+    let column_idx = block_index as usize - (row_idx * data.blocks_per_row as usize);
+
+    let row_start = row_idx * data.inner_size as usize;
+    let row_end = row_start + data.inner_size as usize;
+    let start = row_start + column_idx * BLOCK_SIZE as usize;
+    let end = (start + BLOCK_SIZE as usize).min(row_end);
+
+    let prefix = chained_count(data.temp, block_index as usize, column_idx, &mut sequential,
+      || count_selected(data.predicate, &data.input[start .. end]),
+      |selected_offset| {
+        let rejected_offset = (start - row_start) as u64 - selected_offset;
+        selected_offset + partition_sequential(
+          data.predicate, &data.input[start .. end],
+          &data.selected[row_start + selected_offset as usize .. row_end],
+          &data.rejected[row_start + rejected_offset as usize .. row_end]
+        )
+      }
+    );
+
+    if column_idx == data.blocks_per_row as usize - 1 {
+      data.counts[row_idx].store(prefix, Ordering::Relaxed);
+    }
+  });
+}
+
+fn finish(workers: &Workers, task: *mut TaskObject<Data>) {
+  let _ = unsafe { TaskObject::take_data(task) };
+  workers.finish();
+}
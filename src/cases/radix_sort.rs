@@ -0,0 +1,256 @@
+use core::cell::RefCell;
+use core::fmt::Debug;
+use core::sync::atomic::{Ordering, AtomicU32, AtomicU64};
+use num_format::{Locale, ToFormattedString};
+use crate::cases::scan::{self, columnwise_chained, parallel_fill, random, row_column_chained, Mismatch};
+use crate::cases::scan::row_column_chained::BlockInfo;
+use crate::core::worker::*;
+use crate::utils::array::{alloc_undef_u64_array, Buffer, DynMultArray};
+use crate::utils::benchmark::{benchmark, ChartStyle, Settings};
+use crate::utils::numa::NodeClaims;
+
+mod passes;
+
+use passes::SORT_BLOCK_SIZE;
+
+// Parallel LSD radix sort. Every pass sorts the keys stably on a digit of RADIX_BITS bits, in three phases:
+// 1. Every block of keys computes its histogram of the digit (data parallel).
+// 2. The histograms form a [BUCKETS, block_count] array, of which every row is scanned with the column-wise
+//    chained scan. This gives the offset of every block within every bucket. The start of every bucket
+//    follows from the totals of the rows (sequential, as there are only BUCKETS rows).
+// 3. Every block writes its keys to the positions of their buckets (data parallel).
+// The passes alternate between the input and a buffer. As the number of passes is even, the sorted keys
+// end up in the input.
+
+// Names of all parallel implementations, as used in the benchmarks
+pub const ALGORITHMS: [&str; 1] = ["Radix sort"];
+
+pub const RADIX_BITS: u32 = 8;
+pub const BUCKETS: usize = 1 << RADIX_BITS;
+
+// Bytes moved per element by all passes. Every pass reads the keys in the histogram phase, and reads and writes
+// the keys in the scatter phase. The payload is read and written in the scatter phase.
+fn bytes_per_element<K: RadixKey>(with_payload: bool) -> f64 {
+  let key_bytes = (K::BITS / 8) as f64;
+  let payload_bytes = if with_payload { 2.0 * std::mem::size_of::<u64>() as f64 } else { 0.0 };
+  (K::BITS / RADIX_BITS) as f64 * (3.0 * key_bytes + payload_bytes)
+}
+
+// Keys that can be sorted with the radix sort. The keys are stored in atomics, like the arrays of the scans,
+// such that the workers can share them.
+pub trait RadixKey: parallel_fill::Element + Sized {
+  type Value: Copy + Ord + Debug + Send;
+  const BITS: u32;
+  fn load(&self) -> Self::Value;
+  fn store(&self, value: Self::Value);
+  fn digit(value: Self::Value, shift: u32) -> usize;
+  fn to_u64(value: Self::Value) -> u64;
+  // Deterministic pseudo-random key for the benchmarks
+  fn generate(index: usize) -> Self::Value;
+}
+
+impl RadixKey for AtomicU32 {
+  type Value = u32;
+  const BITS: u32 = 32;
+  #[inline(always)]
+  fn load(&self) -> u32 { AtomicU32::load(self, Ordering::Relaxed) }
+  #[inline(always)]
+  fn store(&self, value: u32) { AtomicU32::store(self, value, Ordering::Relaxed) }
+  #[inline(always)]
+  fn digit(value: u32, shift: u32) -> usize { ((value >> shift) as usize) & (BUCKETS - 1) }
+  fn to_u64(value: u32) -> u64 { value as u64 }
+  fn generate(index: usize) -> u32 { random(index as u64) }
+}
+
+impl RadixKey for AtomicU64 {
+  type Value = u64;
+  const BITS: u32 = 64;
+  #[inline(always)]
+  fn load(&self) -> u64 { AtomicU64::load(self, Ordering::Relaxed) }
+  #[inline(always)]
+  fn store(&self, value: u64) { AtomicU64::store(self, value, Ordering::Relaxed) }
+  #[inline(always)]
+  fn digit(value: u64, shift: u32) -> usize { ((value >> shift) as usize) & (BUCKETS - 1) }
+  fn to_u64(value: u64) -> u64 { value }
+  fn generate(index: usize) -> u64 { ((random(2 * index as u64) as u64) << 32) | random(2 * index as u64 + 1) as u64 }
+}
+
+// Temporary arrays of the radix sort, for inputs of a given length
+pub struct Buffers<K> {
+  keys: Buffer<K>,
+  payload: Buffer<AtomicU64>,
+  counts: DynMultArray<AtomicU64>,
+  offsets: DynMultArray<AtomicU64>,
+  bases: Box<[AtomicU64]>,
+  temp: Buffer<BlockInfo>
+}
+
+impl<K> Buffers<K> {
+  pub fn new(length: usize, with_payload: bool) -> Self {
+    let shape = [BUCKETS, length.div_ceil(SORT_BLOCK_SIZE)];
+    let counts = unsafe { DynMultArray::new(&shape) };
+    let offsets = unsafe { DynMultArray::new(&shape) };
+    let temp = row_column_chained::create_temp(&counts);
+    Buffers{
+      keys: unsafe { alloc_undef_u64_array(length) },
+      payload: unsafe { alloc_undef_u64_array(if with_payload { length } else { 0 }) },
+      counts,
+      offsets,
+      bases: (0 .. BUCKETS).map(|_| AtomicU64::new(0)).collect(),
+      temp
+    }
+  }
+}
+
+// Sorts the keys, and moves the payload along with the keys if present.
+// The buffers must be created for the length of the keys, and with a payload if a payload is given.
+pub fn sort<K: RadixKey>(thread_count: usize, keys: &[K], payload: Option<&[AtomicU64]>, buffers: &Buffers<K>) {
+  assert_eq!(keys.len(), buffers.keys.len());
+  if let Some(payload) = payload {
+    assert_eq!(payload.len(), keys.len());
+    assert_eq!(buffers.payload.len(), keys.len());
+  }
+  if keys.len() <= 1 {
+    return;
+  }
+
+  let block_count = keys.len().div_ceil(SORT_BLOCK_SIZE);
+  let buffer_payload = payload.map(|_| &buffers.payload[..]);
+  for pass in 0 .. K::BITS / RADIX_BITS {
+    let (input, output, input_payload, output_payload) = if pass % 2 == 0 {
+      (keys, &buffers.keys[..], payload, buffer_payload)
+    } else {
+      (&buffers.keys[..], keys, buffer_payload, payload)
+    };
+    let data = || passes::Data{
+      input, input_payload, output, output_payload,
+      counts: buffers.counts.get_data(),
+      offsets: buffers.offsets.get_data(),
+      bases: &buffers.bases,
+      shift: pass * RADIX_BITS,
+      block_count,
+      claims: NodeClaims::new(block_count as u32)
+    };
+
+    Workers::run(thread_count, passes::create_histogram_task(data()));
+    Workers::run(thread_count, columnwise_chained::init_single(&buffers.counts, &buffers.temp, &buffers.offsets));
+
+    let mut base = 0;
+    for bucket in 0 .. BUCKETS {
+      buffers.bases[bucket].store(base, Ordering::Relaxed);
+      base += buffers.offsets.get_data()[bucket * block_count + block_count - 1].load(Ordering::Relaxed);
+    }
+
+    Workers::run(thread_count, passes::create_scatter_task(data()));
+  }
+}
+
+pub fn run_radix_sort(settings: &Settings, sizes: &[usize]) {
+  for &size in sizes {
+    run_for::<AtomicU32>(settings, size, false);
+    run_for::<AtomicU64>(settings, size, false);
+    run_for::<AtomicU64>(settings, size, true);
+  }
+}
+
+fn run_for<K: RadixKey>(settings: &Settings, size: usize, with_payload: bool) {
+  let keys: Buffer<K> = unsafe { alloc_undef_u64_array(size) };
+  let payload: Buffer<AtomicU64> = unsafe { alloc_undef_u64_array(if with_payload { size } else { 0 }) };
+  let buffers = Buffers::<K>::new(size, with_payload);
+  // The sequential reference sorts the keys, or (key, payload) pairs if there is a payload.
+  // As the payload is the original index, this gives the same order as the stable radix sort.
+  let reference_keys = RefCell::new(Vec::new());
+  let reference_pairs = RefCell::new(Vec::new());
+
+  let name = format!("Radix sort u{}{} (n = {})", K::BITS, if with_payload { " with payload" } else { "" }, size.to_formatted_string(&Locale::en));
+  benchmark(
+      settings,
+      ChartStyle::WithKey,
+      &name,
+      &[size],
+      bytes_per_element::<K>(with_payload),
+      || {
+        if with_payload {
+          *reference_pairs.borrow_mut() = (0 .. size).map(|idx| (K::generate(idx), idx as u64)).collect();
+        } else {
+          *reference_keys.borrow_mut() = (0 .. size).map(K::generate).collect();
+        }
+      },
+      || {
+        if with_payload {
+          let mut pairs = reference_pairs.borrow_mut();
+          pairs.sort_unstable();
+          compute_output(size, |idx| K::to_u64(pairs[idx].0), |idx| pairs[idx].1)
+        } else {
+          let mut keys = reference_keys.borrow_mut();
+          keys.sort_unstable();
+          compute_output(size, |idx| K::to_u64(keys[idx]), |_| 0)
+        }
+      }
+    )
+    .parallel("Radix sort", 7, None, true, || { fill(&keys, &payload) }, |thread_count| {
+      sort(thread_count, &keys, if with_payload { Some(&payload) } else { None }, &buffers);
+      compute_output(size, |idx| K::to_u64(keys[idx].load()), |idx| if with_payload { payload[idx].load(Ordering::Relaxed) } else { 0 })
+    });
+}
+
+// Verification of the radix sort, see scan::verify. Every shape is sorted as a one-dimensional array.
+pub fn verify(settings: &Settings, shapes: &[Vec<usize>]) -> bool {
+  let mut correct = true;
+  for shape in shapes {
+    let size = shape.iter().product();
+    correct &= verify_for::<AtomicU32>(settings, size, false);
+    correct &= verify_for::<AtomicU64>(settings, size, false);
+    correct &= verify_for::<AtomicU64>(settings, size, true);
+  }
+  correct
+}
+
+fn verify_for<K: RadixKey>(settings: &Settings, size: usize, with_payload: bool) -> bool {
+  let keys: Buffer<K> = unsafe { alloc_undef_u64_array(size) };
+  let payload: Buffer<AtomicU64> = unsafe { alloc_undef_u64_array(if with_payload { size } else { 0 }) };
+  let buffers = Buffers::<K>::new(size, with_payload);
+  // Sorting (key, original index) pairs gives the order of a stable sort. The payload is empty without a payload.
+  let mut pairs: Vec<(K::Value, u64)> = (0 .. size).map(|idx| (K::generate(idx), idx as u64)).collect();
+  pairs.sort_unstable();
+  let expected_keys: Vec<u64> = pairs.iter().map(|&(key, _)| K::to_u64(key)).collect();
+  let expected_payload: Vec<u64> = pairs.iter().take(payload.len()).map(|&(_, idx)| idx).collect();
+
+  println!("Verifying radix sort u{}{} of {} elements", K::BITS, if with_payload { " with payload" } else { "" }, size);
+  let mut correct = true;
+  for name in ALGORITHMS {
+    if !settings.includes_algorithm(name) {
+      continue;
+    }
+    for &thread_count in &settings.thread_counts {
+      correct &= scan::verify_case(name, thread_count, "in-place", || {
+        fill(&keys, &payload);
+        sort(thread_count, &keys, if with_payload { Some(&payload) } else { None }, &buffers);
+        find_key_mismatch(&expected_keys, &keys)
+          .or_else(|| scan::find_mismatch("payload", &expected_payload, &payload, size))
+      });
+    }
+  }
+  correct
+}
+
+fn find_key_mismatch<K: RadixKey>(expected: &[u64], keys: &[K]) -> Option<Mismatch> {
+  expected.iter().zip(keys).position(|(&expected, key)| expected != K::to_u64(key.load()))
+    .map(|index| Mismatch{ array: "keys", index, row_length: keys.len(), expected: expected[index], actual: K::to_u64(keys[index].load()) })
+}
+
+fn fill<K: RadixKey>(keys: &[K], payload: &[AtomicU64]) {
+  parallel_fill::fill_with(keys, |idx| K::to_u64(K::generate(idx)));
+  parallel_fill::fill_with(payload, |idx| idx as u64);
+}
+
+// Cheap checksum of a few keys and payloads, to detect wrong results during the benchmarks
+fn compute_output<Key: Fn(usize) -> u64, Payload: Fn(usize) -> u64>(length: usize, key: Key, payload: Payload) -> u64 {
+  if length == 0 {
+    return 0;
+  }
+  let last = length - 1;
+  [1.min(last), 98238.min(last), length.saturating_sub(123), last].iter()
+    .map(|&idx| key(idx).wrapping_add(payload(idx).wrapping_mul(31)))
+    .fold(0, u64::wrapping_add)
+}
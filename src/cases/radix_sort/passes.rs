@@ -0,0 +1,93 @@
+use core::sync::atomic::{Ordering, AtomicU64};
+use crate::cases::radix_sort::{RadixKey, BUCKETS};
+use crate::core::worker::*;
+use crate::core::task::*;
+use crate::core::workassisting_loop::*;
+use crate::utils::numa::NodeClaims;
+
+// The two data-parallel phases of a pass of the LSD radix sort.
+// The input is divided in blocks of SORT_BLOCK_SIZE keys. The histogram of a block is stored in
+// a column of 'counts', which has shape [BUCKETS, block_count]. After the histogram phase,
+// 'offsets' contains the per-bucket inclusive scan of 'counts' and 'bases' the start of every bucket.
+// The scatter phase then writes the keys of a block to the positions of its buckets, preserving the
+// order within a block, such that every pass is stable.
+// The blocks of both phases are independent, hence workers prefer the blocks on their own NUMA node.
+
+// Number of keys per block of the histogram and scatter phases
+pub const SORT_BLOCK_SIZE: usize = 1024 * 16;
+
+pub struct Data<'a, K> {
+  pub input: &'a [K],
+  pub input_payload: Option<&'a [AtomicU64]>,
+  pub output: &'a [K],
+  pub output_payload: Option<&'a [AtomicU64]>,
+  pub counts: &'a [AtomicU64],
+  pub offsets: &'a [AtomicU64],
+  pub bases: &'a [AtomicU64],
+  pub shift: u32,
+  pub block_count: usize,
+  pub claims: NodeClaims
+}
+
+pub fn create_histogram_task<K: RadixKey>(data: Data<K>) -> Task {
+  let block_count = data.block_count as u32;
+  Task::new_dataparallel::<Data<K>>(run_histogram::<K>, finish::<K>, data, block_count, false)
+}
+
+pub fn create_scatter_task<K: RadixKey>(data: Data<K>) -> Task {
+  let block_count = data.block_count as u32;
+  Task::new_dataparallel::<Data<K>>(run_scatter::<K>, finish::<K>, data, block_count, false)
+}
+
+fn block_bounds<K>(data: &Data<K>, block_index: u32) -> (usize, usize) {
+  let start = block_index as usize * SORT_BLOCK_SIZE;
+  let end = (start + SORT_BLOCK_SIZE).min(data.input.len());
+  (start, end)
+}
+
+fn run_histogram<K: RadixKey>(_workers: &Workers, task: *const TaskObject<Data<K>>, loop_arguments: LoopArguments) {
+  let data = unsafe { TaskObject::get_data(task) };
+
+  workassisting_loop_numa!(loop_arguments, &data.claims, |block_index| {
+    let (start, end) = block_bounds(data, block_index);
+    let mut histogram = [0; BUCKETS];
+    for key in &data.input[start .. end] {
+      histogram[K::digit(key.load(), data.shift)] += 1;
+    }
+    for (bucket, &count) in histogram.iter().enumerate() {
+      data.counts[bucket * data.block_count + block_index as usize].store(count, Ordering::Relaxed);
+    }
+  });
+}
+
+fn run_scatter<K: RadixKey>(_workers: &Workers, task: *const TaskObject<Data<K>>, loop_arguments: LoopArguments) {
+  let data = unsafe { TaskObject::get_data(task) };
+
+  workassisting_loop_numa!(loop_arguments, &data.claims, |block_index| {
+    let (start, end) = block_bounds(data, block_index);
+    // Position of the first key of every bucket of this block in the output
+    let mut positions = [0; BUCKETS];
+    for (bucket, position) in positions.iter_mut().enumerate() {
+      let idx = bucket * data.block_count + block_index as usize;
+      *position = data.bases[bucket].load(Ordering::Relaxed)
+        + data.offsets[idx].load(Ordering::Relaxed)
+        - data.counts[idx].load(Ordering::Relaxed);
+    }
+
+    for i in start .. end {
+      let key = data.input[i].load();
+      let bucket = K::digit(key, data.shift);
+      let position = positions[bucket] as usize;
+      data.output[position].store(key);
+      if let (Some(input_payload), Some(output_payload)) = (data.input_payload, data.output_payload) {
+        output_payload[position].store(input_payload[i].load(Ordering::Relaxed), Ordering::Relaxed);
+      }
+      positions[bucket] += 1;
+    }
+  });
+}
+
+fn finish<K: RadixKey>(workers: &Workers, task: *mut TaskObject<Data<K>>) {
+  let _ = unsafe { TaskObject::take_data(task) };
+  workers.finish();
+}
@@ -0,0 +1,241 @@
+use core::fmt::Debug;
+use core::sync::atomic::{Ordering, AtomicU64};
+use crate::cases::scan::{self, parallel_fill, random, BLOCK_SIZE};
+use crate::cases::scan::row_column_chained::STATE_INITIALIZED;
+use crate::core::worker::*;
+use crate::utils;
+use crate::utils::array::{alloc_undef_u64_array, Buffer, Multidimensional};
+use crate::utils::benchmark::{benchmark, ChartStyle, Settings};
+
+pub mod row_column_chained;
+
+// First-order linear recurrences y[i] = a[i] * y[i-1] + b[i], with y[-1] = 0, solved along every innermost row.
+// This is a scan over affine maps y -> a * y + b, where combining two maps is function composition.
+// The values are stored as bits in AtomicU64s, like the arrays of the other cases.
+
+// Names of all parallel implementations, as used in the benchmarks
+pub const ALGORITHMS: [&str; 1] = ["Chained recurrence"];
+
+// Both coefficients are read and the output is written, 8 bytes each
+const BYTES_PER_ELEMENT: f64 = 24.0;
+
+// Element type of a recurrence
+pub trait Element: Copy + Send + Sync + 'static {
+  const ZERO: Self;
+  const ONE: Self;
+  // Checksum of the benchmarks, compared against the sequential reference
+  type Checksum: Copy + Debug + Eq + Send;
+  const NAME: &'static str;
+
+  fn from_bits(bits: u64) -> Self;
+  fn to_bits(self) -> u64;
+  fn add(self, other: Self) -> Self;
+  fn mul(self, other: Self) -> Self;
+  fn checksum(values: &[AtomicU64]) -> Self::Checksum;
+  // Whether an element of the output equals the element of the sequential reference, used by the verification
+  fn output_equal(expected: Self, actual: Self) -> bool;
+  // Deterministic pseudo-random coefficients for the benchmarks
+  fn generate_a(index: usize) -> Self;
+  fn generate_b(index: usize) -> Self;
+}
+
+impl Element for f64 {
+  const ZERO: f64 = 0.0;
+  const ONE: f64 = 1.0;
+  type Checksum = Approximate;
+  const NAME: &'static str = "f64";
+
+  #[inline(always)]
+  fn from_bits(bits: u64) -> f64 { f64::from_bits(bits) }
+  #[inline(always)]
+  fn to_bits(self) -> u64 { f64::to_bits(self) }
+  #[inline(always)]
+  fn add(self, other: f64) -> f64 { self + other }
+  #[inline(always)]
+  fn mul(self, other: f64) -> f64 { self * other }
+  fn checksum(values: &[AtomicU64]) -> Approximate {
+    Approximate(checksum_indices(values.len()).map(|idx| f64::from_bits(values[idx].load(Ordering::Relaxed))).sum())
+  }
+  fn output_equal(expected: f64, actual: f64) -> bool { Approximate(expected) == Approximate(actual) }
+  // Between 0.5 and 0.99, such that the filter is stable and the values stay bounded
+  fn generate_a(index: usize) -> f64 { 0.5 + 0.49 * unit(random(2 * index as u64 + 1)) }
+  fn generate_b(index: usize) -> f64 { unit(random(2 * index as u64 + 2)) }
+}
+
+impl Element for u64 {
+  const ZERO: u64 = 0;
+  const ONE: u64 = 1;
+  type Checksum = u64;
+  const NAME: &'static str = "u64";
+
+  #[inline(always)]
+  fn from_bits(bits: u64) -> u64 { bits }
+  #[inline(always)]
+  fn to_bits(self) -> u64 { self }
+  #[inline(always)]
+  fn add(self, other: u64) -> u64 { self.wrapping_add(other) }
+  #[inline(always)]
+  fn mul(self, other: u64) -> u64 { self.wrapping_mul(other) }
+  fn checksum(values: &[AtomicU64]) -> u64 {
+    checksum_indices(values.len()).map(|idx| values[idx].load(Ordering::Relaxed)).fold(0, u64::wrapping_add)
+  }
+  fn output_equal(expected: u64, actual: u64) -> bool { expected == actual }
+  fn generate_a(index: usize) -> u64 { random(2 * index as u64 + 1) as u64 }
+  fn generate_b(index: usize) -> u64 { random(2 * index as u64 + 2) as u64 }
+}
+
+fn unit(value: u32) -> f64 {
+  value as f64 / u32::MAX as f64
+}
+
+// Same indices as the checksum of the scans
+fn checksum_indices(length: usize) -> impl Iterator<Item = usize> {
+  let last = length.saturating_sub(1);
+  let indices = if length == 0 { vec![] } else { vec![1.min(last), 98238.min(last), length.saturating_sub(123), last] };
+  indices.into_iter()
+}
+
+// Checksum of floating point outputs. The parallel algorithm composes the maps in a different order
+// than the sequential algorithm, which gives different rounding errors. Hence we compare with a relative tolerance.
+#[derive(Copy, Clone, Debug)]
+pub struct Approximate(pub f64);
+
+impl PartialEq for Approximate {
+  fn eq(&self, other: &Approximate) -> bool {
+    (self.0 - other.0).abs() <= 1e-9 * self.0.abs().max(other.0.abs()).max(1.0)
+  }
+}
+impl Eq for Approximate {}
+
+// Descriptor of a block in the look-back. The aggregate is the affine map of the block,
+// the prefix is the value of y at the end of the block.
+pub struct BlockInfo {
+  pub state: AtomicU64,
+  pub aggregate_a: AtomicU64,
+  pub aggregate_b: AtomicU64,
+  pub prefix: AtomicU64
+}
+
+pub fn create_temp<A: Multidimensional<AtomicU64>>(input: &A) -> Buffer<BlockInfo> {
+  let count = (input.get_inner_size() as u64).div_ceil(BLOCK_SIZE) * input.total_inner_count() as u64;
+  let temp = unsafe { alloc_undef_u64_array(count as usize) };
+  reset(&temp);
+  temp
+}
+
+pub fn reset(temp: &[BlockInfo]) {
+  for info in temp {
+    info.state.store(STATE_INITIALIZED, Ordering::Relaxed);
+    info.aggregate_a.store(0, Ordering::Relaxed);
+    info.aggregate_b.store(0, Ordering::Relaxed);
+    info.prefix.store(0, Ordering::Relaxed);
+  }
+}
+
+pub fn run_recurrence(settings: &Settings, shapes: &[Vec<usize>]) { // Linear recurrence along every innermost row
+  for shape in shapes {
+    run_for::<f64>(settings, shape);
+    run_for::<u64>(settings, shape);
+  }
+}
+
+fn run_for<E: Element>(settings: &Settings, shape: &[usize]) {
+  let a = unsafe { utils::array::DynMultArray::new(shape) };
+  let b = unsafe { utils::array::DynMultArray::new(shape) };
+  let output = unsafe { utils::array::DynMultArray::new(shape) };
+  let temp = create_temp(&a);
+  fill::<E>(a.get_data(), b.get_data());
+
+  let name = format!("Linear recurrence {} (sh = {:?})", E::NAME, shape);
+  benchmark(
+      settings,
+      ChartStyle::WithKey,
+      &name,
+      shape,
+      BYTES_PER_ELEMENT,
+      || {},
+      || { reference_sequential::<E, _>(&a, &b, &output) }
+    )
+    .parallel("Chained recurrence", 7, None, true, || {}, |thread_count| {
+      let task = row_column_chained::init_single::<E, _>(&a, &b, &temp, &output);
+      Workers::run(thread_count, task);
+      E::checksum(output.get_data())
+    });
+}
+
+// Verification of the recurrence, see scan::verify. Every element of the output is compared with
+// Element::output_equal, as floating point outputs may differ in rounding.
+pub fn verify(settings: &Settings, shapes: &[Vec<usize>]) -> bool {
+  let mut correct = true;
+  for shape in shapes {
+    correct &= verify_for::<f64>(settings, shape);
+    correct &= verify_for::<u64>(settings, shape);
+  }
+  correct
+}
+
+fn verify_for<E: Element>(settings: &Settings, shape: &[usize]) -> bool {
+  let a = unsafe { utils::array::DynMultArray::new(shape) };
+  let b = unsafe { utils::array::DynMultArray::new(shape) };
+  let output = unsafe { utils::array::DynMultArray::new(shape) };
+  let temp = create_temp(&a);
+  fill::<E>(a.get_data(), b.get_data());
+  reference_sequential::<E, _>(&a, &b, &output);
+  let expected = scan::snapshot(output.get_data());
+
+  println!("Verifying linear recurrence {} of shape {:?}", E::NAME, shape);
+  let mut correct = true;
+  for name in ALGORITHMS {
+    if !settings.includes_algorithm(name) {
+      continue;
+    }
+    for &thread_count in &settings.thread_counts {
+      correct &= scan::verify_case(name, thread_count, "out-of-place", || {
+        scan::clear(output.get_data());
+        Workers::run(thread_count, row_column_chained::init_single::<E, _>(&a, &b, &temp, &output));
+        scan::find_mismatch_by("output", &expected, output.get_data(), a.get_inner_size(), |expected, actual| {
+          E::output_equal(E::from_bits(expected), E::from_bits(actual))
+        })
+      });
+    }
+  }
+  correct
+}
+
+pub fn fill<E: Element>(a: &[AtomicU64], b: &[AtomicU64]) {
+  parallel_fill::fill_with(a, |idx| E::generate_a(idx).to_bits());
+  parallel_fill::fill_with(b, |idx| E::generate_b(idx).to_bits());
+}
+
+pub fn reference_sequential<E: Element, A: Multidimensional<AtomicU64>>(a: &A, b: &A, output: &A) -> E::Checksum {
+  let row_length = a.get_inner_size();
+  for row in 0 .. a.total_inner_count() {
+    let range = row * row_length .. (row + 1) * row_length;
+    solve_sequential::<E>(&a.get_data()[range.clone()], &b.get_data()[range.clone()], E::ZERO, &output.get_data()[range]);
+  }
+  E::checksum(output.get_data())
+}
+
+// Solves the recurrence starting from y[-1] = initial. Returns the last value of y, or 'initial' if the input is empty.
+pub fn solve_sequential<E: Element>(a: &[AtomicU64], b: &[AtomicU64], initial: E, output: &[AtomicU64]) -> E {
+  assert_eq!(a.len(), output.len());
+  assert_eq!(b.len(), output.len());
+  let mut y = initial;
+  for ((a, b), output) in a.iter().zip(b).zip(output) {
+    y = E::from_bits(a.load(Ordering::Relaxed)).mul(y).add(E::from_bits(b.load(Ordering::Relaxed)));
+    output.store(y.to_bits(), Ordering::Relaxed);
+  }
+  y
+}
+
+// Composes the maps of all elements, giving the map (A, B) such that y[end] = A * y[start - 1] + B
+pub fn fold_sequential<E: Element>(a: &[AtomicU64], b: &[AtomicU64]) -> (E, E) {
+  let mut map_a = E::ONE;
+  let mut map_b = E::ZERO;
+  for (a, b) in a.iter().zip(b) {
+    let element_a = E::from_bits(a.load(Ordering::Relaxed));
+    map_a = element_a.mul(map_a);
+    map_b = element_a.mul(map_b).add(E::from_bits(b.load(Ordering::Relaxed)));
+  }
+  (map_a, map_b)
+}
@@ -0,0 +1,153 @@
+use core::marker::PhantomData;
+use core::sync::atomic::{Ordering, AtomicU64, AtomicU32};
+use crate::cases::recurrence::{fold_sequential, solve_sequential, reset, BlockInfo, Element};
+use crate::cases::scan::BLOCK_SIZE;
+use crate::cases::scan::row_column_chained::{ STATE_PREFIX_AVAILABLE, STATE_AGGREGATE_AVAILABLE };
+use crate::core::worker::*;
+use crate::core::task::*;
+use crate::core::trace;
+use crate::core::workassisting_loop::*;
+use crate::utils::array::Multidimensional;
+
+// First-order linear recurrence y[i] = a[i] * y[i-1] + b[i] along every innermost row, with y[-1] = 0,
+// using the adaptive row-wise / column-wise chained strategy of the assisting column-wise chained scan.
+// The aggregate of a block is the affine map y -> A * y + B of the whole block, and the prefix of a block is
+// the value of y at the end of the block. In the look-back, the maps of the predecessors are composed until
+// a predecessor with a prefix is found.
+
+struct Data<'a, E> {
+  a: &'a [AtomicU64],
+  b: &'a [AtomicU64],
+  temp: &'a [BlockInfo],
+  output: &'a [AtomicU64],
+  blocks_per_row: u64,
+  inner_size: u64,
+  element: PhantomData<E>
+}
+
+pub fn init_single<E: Element, A: Multidimensional<AtomicU64>>(a: &A, b: &A, temp: &[BlockInfo], output: &A) -> Task {
+  reset(temp);
+  create_task::<E, A>(a, b, temp, output)
+}
+
+fn create_task<E: Element, A: Multidimensional<AtomicU64>>(a_m: &A, b_m: &A, temp: &[BlockInfo], output_m: &A) -> Task {
+  let inner_size = a_m.get_inner_size() as u64;
+  let inner_rows = a_m.total_inner_count() as u64;
+
+  let blocks_per_row = inner_size.div_ceil(BLOCK_SIZE);
+  let block_count = if inner_size == 0 {
+        // Empty rows have no output, and run would divide by the row length
+        0
+      } else if blocks_per_row > 1 {
+        blocks_per_row.checked_mul(inner_rows).expect("Block count overflowed u64 size") as u32
+      } else {
+        // Multiple rows are (optionally) combined into a single block, which changes the total block_count
+        inner_rows.div_ceil(BLOCK_SIZE / inner_size) as u32
+      };
+
+  let data = Data{ a: a_m.get_data(), b: b_m.get_data(), temp, output: output_m.get_data(), blocks_per_row, inner_size, element: PhantomData::<E> };
+  Task::new_dataparallel::<Data<E>>(run::<E>, finish::<E>, data, block_count, true)
+}
+
+fn run<E: Element>(_workers: &Workers, task: *const TaskObject<Data<E>>, loop_arguments: LoopArguments) {
+  let data = unsafe { TaskObject::get_data(task) };
+  let inner_rows = data.a.len() / data.inner_size as usize;
+  let segments = data.blocks_per_row as u32;
+
+  workassisting_loop_row_column!(loop_arguments, segments,
+  // Multiple-rows algorithm
+  |block_index| {
+    let rows_per_block = (BLOCK_SIZE as usize / data.inner_size as usize).min(inner_rows);
+    let first_row = block_index as usize * rows_per_block;
+
+    for row in first_row .. (first_row + rows_per_block).min(inner_rows) {
+      let start = row * data.inner_size as usize;
+      let end = start + data.inner_size as usize;
+      solve_sequential(&data.a[start .. end], &data.b[start .. end], E::ZERO, &data.output[start .. end]);
+    }
+  },
+  // Row-wise algorithm
+  |block_index| {
+    let row_idx = block_index as usize / data.blocks_per_row as usize;
+    let column_idx = block_index as usize - (row_idx * data.blocks_per_row as usize);
+    adaptive_chained_lookback(data, row_idx, column_idx, block_index as usize);
+  },
+  // Column-wise algorithm
+  |block_index, rows_completed| {
+    let new_inner_rows = inner_rows - rows_completed as usize;
+    let row_idx = (block_index as usize % new_inner_rows) + rows_completed as usize;
+    let column_idx = block_index as usize / new_inner_rows;
+    let descriptor_idx = row_idx * data.blocks_per_row as usize + column_idx;
+    adaptive_chained_lookback(data, row_idx, column_idx, descriptor_idx);
+  });
+}
+
+fn adaptive_chained_lookback<E: Element>(data: &Data<'_, E>, row_idx: usize, column_idx: usize, descriptor_idx: usize) {
+  let start = row_idx * data.inner_size as usize + column_idx * BLOCK_SIZE as usize;
+  let end = row_idx * data.inner_size as usize + ((column_idx + 1) * BLOCK_SIZE as usize).min(data.inner_size as usize);
+  let a = &data.a[start .. end];
+  let b = &data.b[start .. end];
+  let output = &data.output[start .. end];
+
+  // Check if we already have the prefix of the previous block or
+  // if the current block is at the start of a row.
+  // If that is the case, then we can solve the recurrence of this block directly.
+  // Otherwise we first compute the affine map of this block and perform a look-back.
+  let prefix_start = if column_idx == 0 {
+    Some(E::ZERO)
+  } else {
+    let previous = &data.temp[descriptor_idx - 1];
+    if previous.state.load(Ordering::Acquire) == STATE_PREFIX_AVAILABLE {
+      Some(E::from_bits(previous.prefix.load(Ordering::Acquire)))
+    } else {
+      None
+    }
+  };
+
+  if let Some(prefix) = prefix_start {
+    let local = solve_sequential(a, b, prefix, output);
+    data.temp[descriptor_idx].prefix.store(local.to_bits(), Ordering::Relaxed);
+    data.temp[descriptor_idx].state.store(STATE_PREFIX_AVAILABLE, Ordering::Release);
+  } else {
+    let (local_a, local_b) = fold_sequential::<E>(a, b);
+    data.temp[descriptor_idx].aggregate_a.store(local_a.to_bits(), Ordering::Relaxed);
+    data.temp[descriptor_idx].aggregate_b.store(local_b.to_bits(), Ordering::Relaxed);
+    data.temp[descriptor_idx].state.store(STATE_AGGREGATE_AVAILABLE, Ordering::Release);
+
+    // Look-back phase -- composing the maps of the predecessors, starting with the identity map
+    let mut map_a = E::ONE;
+    let mut map_b = E::ZERO;
+    let mut previous = descriptor_idx - 1;
+
+    let lookback_span = trace::start();
+    let prefix = loop {
+      let info = &data.temp[previous];
+      let previous_state = info.state.load(Ordering::Acquire);
+      if previous_state == STATE_PREFIX_AVAILABLE {
+        let previous_prefix = E::from_bits(info.prefix.load(Ordering::Acquire));
+        break map_a.mul(previous_prefix).add(map_b);
+      } else if previous_state == STATE_AGGREGATE_AVAILABLE {
+        let previous_a = E::from_bits(info.aggregate_a.load(Ordering::Acquire));
+        let previous_b = E::from_bits(info.aggregate_b.load(Ordering::Acquire));
+        // The map of the predecessor is applied before the maps that were already composed
+        map_b = map_a.mul(previous_b).add(map_b);
+        map_a = map_a.mul(previous_a);
+        previous -= 1;
+      } else {
+        // Continue looping until the state of the previous block changes.
+      }
+    };
+    trace::complete(lookback_span, "look-back", trace::NO_ARGS);
+
+    // Share calculated prefix value
+    data.temp[descriptor_idx].prefix.store(local_a.mul(prefix).add(local_b).to_bits(), Ordering::Relaxed);
+    data.temp[descriptor_idx].state.store(STATE_PREFIX_AVAILABLE, Ordering::Release);
+
+    solve_sequential(a, b, prefix, output);
+  }
+}
+
+fn finish<E: Element>(workers: &Workers, task: *mut TaskObject<Data<E>>) {
+  let _ = unsafe { TaskObject::take_data(task) };
+  workers.finish();
+}
@@ -0,0 +1,177 @@
+use core::sync::atomic::{Ordering, AtomicU64};
+use num_format::{Locale, ToFormattedString};
+use crate::cases::scan::{self, fill, fold_sequential, row_column_chained};
+use crate::core::worker::*;
+use crate::utils;
+use crate::utils::array::Multidimensional;
+use crate::utils::benchmark::{benchmark, ChartStyle, Settings};
+
+pub mod row_column;
+
+use row_column::Operation;
+
+// Reductions and histograms of every innermost row, for workloads that only need the totals of the rows.
+// They use the scheduling of the assisting column-wise chained scan, but skip the look-back and the output.
+// A one-dimensional array is reduced as a single row.
+
+// Names of all parallel implementations, as used in the benchmarks
+pub const ALGORITHMS: [&str; 2] = ["Assisting column-wise reduce", "Assisting column-wise histogram"];
+
+// Number of buckets of the histograms
+pub const BUCKETS: usize = 256;
+
+// The reductions and histograms only read the input, hence the bandwidth counts one read of 8 bytes per element.
+// The results per row are negligible.
+const BYTES_PER_ELEMENT: f64 = 8.0;
+
+// Bucket of the histograms in the benchmarks
+fn low_byte(value: u64) -> usize {
+  (value & (BUCKETS as u64 - 1)) as usize
+}
+
+pub fn run_reduce(settings: &Settings, sizes: &[usize]) { // One-dimensional reduction
+  for &size in sizes {
+    reduce_benchmark(settings, &[size], "Reduce (n = ".to_owned() + &size.to_formatted_string(&Locale::en) + ")");
+  }
+}
+
+pub fn run_reduce_multidim(settings: &Settings, shapes: &[Vec<usize>]) { // Reduction of every innermost row
+  for shape in shapes {
+    reduce_benchmark(settings, shape, "Segmented reduce (sh = ".to_owned() + &format!("{:?}", shape) + ")");
+  }
+}
+
+pub fn run_histogram(settings: &Settings, sizes: &[usize]) { // One-dimensional histogram
+  for &size in sizes {
+    histogram_benchmark(settings, &[size], "Histogram (n = ".to_owned() + &size.to_formatted_string(&Locale::en) + ")");
+  }
+}
+
+pub fn run_histogram_multidim(settings: &Settings, shapes: &[Vec<usize>]) { // Histogram of every innermost row
+  for shape in shapes {
+    histogram_benchmark(settings, shape, "Segmented histogram (sh = ".to_owned() + &format!("{:?}", shape) + ")");
+  }
+}
+
+fn reduce_benchmark(settings: &Settings, shape: &[usize], name: String) {
+  let input = unsafe { utils::array::DynMultArray::new(shape) };
+  let totals: Box<[AtomicU64]> = (0 .. input.total_inner_count()).map(|_| AtomicU64::new(0)).collect();
+  // The scan, of which the last column gives the totals, for comparison
+  let output = unsafe { utils::array::DynMultArray::new(shape) };
+  let temp = row_column_chained::create_temp(&input);
+  fill(input.get_data());
+
+  benchmark(
+      settings,
+      ChartStyle::WithKey,
+      &name,
+      shape,
+      BYTES_PER_ELEMENT,
+      || {},
+      || { reference_reduce(&input, &totals) }
+    )
+    .parallel("Assisting column-wise reduce", 7, None, true, || { row_column::reset(&totals) }, |thread_count| {
+      let task = row_column::init_single(Operation::Sum, &input, &totals);
+      Workers::run(thread_count, task);
+      compute_output(&totals)
+    })
+    .parallel("Assisting column-wise chained", 6, None, false, || {}, |thread_count| {
+      let task = row_column_chained::init_single(&input, &temp, &output);
+      Workers::run(thread_count, task);
+      let row_length = input.get_inner_size();
+      for (row, total) in totals.iter().enumerate() {
+        let last = if row_length == 0 { 0 } else { output.get_data()[(row + 1) * row_length - 1].load(Ordering::Relaxed) };
+        total.store(last, Ordering::Relaxed);
+      }
+      compute_output(&totals)
+    });
+}
+
+fn histogram_benchmark(settings: &Settings, shape: &[usize], name: String) {
+  let input = unsafe { utils::array::DynMultArray::new(shape) };
+  let histograms: Box<[AtomicU64]> = (0 .. input.total_inner_count() * BUCKETS).map(|_| AtomicU64::new(0)).collect();
+  fill(input.get_data());
+
+  benchmark(
+      settings,
+      ChartStyle::WithKey,
+      &name,
+      shape,
+      BYTES_PER_ELEMENT,
+      || {},
+      || { reference_histogram(low_byte, &input, &histograms) }
+    )
+    .parallel("Assisting column-wise histogram", 7, None, true, || { row_column::reset(&histograms) }, |thread_count| {
+      let task = row_column::init_single(Operation::Histogram(low_byte), &input, &histograms);
+      Workers::run(thread_count, task);
+      compute_output(&histograms)
+    });
+}
+
+// Verification of the reductions and histograms, see scan::verify. The results of all rows are compared.
+pub fn verify(settings: &Settings, shapes: &[Vec<usize>]) -> bool {
+  let mut correct = true;
+  for shape in shapes {
+    let input = unsafe { utils::array::DynMultArray::new(shape) };
+    let totals: Box<[AtomicU64]> = (0 .. input.total_inner_count()).map(|_| AtomicU64::new(0)).collect();
+    let histograms: Box<[AtomicU64]> = (0 .. input.total_inner_count() * BUCKETS).map(|_| AtomicU64::new(0)).collect();
+    fill(input.get_data());
+    reference_reduce(&input, &totals);
+    reference_histogram(low_byte, &input, &histograms);
+    let expected_totals = scan::snapshot(&totals);
+    let expected_histograms = scan::snapshot(&histograms);
+
+    println!("Verifying reduce and histogram of shape {:?}", shape);
+    for name in ALGORITHMS {
+      if !settings.includes_algorithm(name) {
+        continue;
+      }
+      let (operation, results, expected, row_length) = match name {
+        "Assisting column-wise reduce" => (Operation::Sum, &totals, &expected_totals, 1),
+        "Assisting column-wise histogram" => (Operation::Histogram(low_byte), &histograms, &expected_histograms, BUCKETS),
+        _ => panic!("Unknown reduce algorithm {}", name)
+      };
+      for &thread_count in &settings.thread_counts {
+        correct &= scan::verify_case(name, thread_count, "out-of-place", || {
+          row_column::reset(results);
+          Workers::run(thread_count, row_column::init_single(operation, &input, results));
+          scan::find_mismatch("results", expected, results, row_length)
+        });
+      }
+    }
+  }
+  correct
+}
+
+// Number of elements of 'input' per bucket
+pub fn histogram_sequential(bucket: fn(u64) -> usize, input: &[AtomicU64]) -> [u64; BUCKETS] {
+  let mut histogram = [0; BUCKETS];
+  for value in input {
+    histogram[bucket(value.load(Ordering::Relaxed))] += 1;
+  }
+  histogram
+}
+
+pub fn reference_reduce<A: Multidimensional<AtomicU64>>(input: &A, totals: &[AtomicU64]) -> u64 {
+  let row_length = input.get_inner_size();
+  for (row, total) in totals.iter().enumerate() {
+    total.store(fold_sequential(&input.get_data()[row * row_length .. (row + 1) * row_length]), Ordering::Relaxed);
+  }
+  compute_output(totals)
+}
+
+pub fn reference_histogram<A: Multidimensional<AtomicU64>>(bucket: fn(u64) -> usize, input: &A, histograms: &[AtomicU64]) -> u64 {
+  let row_length = input.get_inner_size();
+  for (row, results) in histograms.chunks(BUCKETS).enumerate() {
+    let histogram = histogram_sequential(bucket, &input.get_data()[row * row_length .. (row + 1) * row_length]);
+    for (result, count) in results.iter().zip(histogram) {
+      result.store(count, Ordering::Relaxed);
+    }
+  }
+  compute_output(histograms)
+}
+
+// Checksum of all results, weighted by their position
+pub fn compute_output(results: &[AtomicU64]) -> u64 {
+  results.iter().enumerate().fold(0, |checksum, (idx, result)| checksum.wrapping_add(result.load(Ordering::Relaxed).wrapping_mul(idx as u64 + 1)))
+}
@@ -0,0 +1,132 @@
+use core::sync::atomic::{Ordering, AtomicU64, AtomicU32};
+use crate::cases::reduce::{histogram_sequential, BUCKETS};
+use crate::cases::scan::{fold_sequential, parallel_fill, BLOCK_SIZE};
+use crate::core::worker::*;
+use crate::core::task::*;
+use crate::core::workassisting_loop::*;
+use crate::utils::array::Multidimensional;
+
+// Reduction and histogram of every innermost row, with the row-wise / column-wise scheduling of the
+// assisting column-wise chained scan. As a reduction does not need the prefix of the previous block,
+// there is no look-back and nothing is written per element. A block that contains complete rows stores
+// their results directly. Otherwise the blocks of a row add their local result to the result of the row,
+// which must be zero when the task starts (see reset). The reset is not part of the task, such that the
+// benchmarks can perform it outside the timed region.
+
+// The operation performed on every row
+#[derive(Copy, Clone)]
+pub enum Operation {
+  // Sum of the row, stored in element 'row' of the results
+  Sum,
+  // Number of elements of the row per bucket, stored in the BUCKETS elements starting at 'row * BUCKETS'
+  Histogram(fn(u64) -> usize)
+}
+
+impl Operation {
+  fn result_length(self) -> usize {
+    match self {
+      Operation::Sum => 1,
+      Operation::Histogram(_) => BUCKETS
+    }
+  }
+}
+
+struct Data<'a> {
+  input: &'a [AtomicU64],
+  results: &'a [AtomicU64],
+  operation: Operation,
+  blocks_per_row: u64,
+  inner_size: u64
+}
+
+// Sets all results to zero, before a task of init_single
+pub fn reset(results: &[AtomicU64]) {
+  parallel_fill::fill_with(results, |_| 0);
+}
+
+// The results must be zero, see reset
+pub fn init_single<A: Multidimensional<AtomicU64>>(operation: Operation, input: &A, results: &[AtomicU64]) -> Task {
+  let inner_size = input.get_inner_size() as u64;
+  let inner_rows = input.total_inner_count() as u64;
+  assert_eq!(results.len(), inner_rows as usize * operation.result_length());
+
+  let blocks_per_row = inner_size.div_ceil(BLOCK_SIZE);
+  let block_count = if inner_size == 0 {
+        // Nothing to reduce, and run would divide by the row length
+        0
+      } else if blocks_per_row > 1 {
+        blocks_per_row.checked_mul(inner_rows).expect("Block count overflowed u64 size") as u32
+      } else {
+        // Multiple rows are (optionally) combined into a single block, which changes the total block_count
+        inner_rows.div_ceil(BLOCK_SIZE / inner_size) as u32
+      };
+
+  let data = Data{ input: input.get_data(), results, operation, blocks_per_row, inner_size };
+  Task::new_dataparallel::<Data>(run, finish, data, block_count, true)
+}
+
+fn run(_workers: &Workers, task: *const TaskObject<Data>, loop_arguments: LoopArguments) {
+  let data = unsafe { TaskObject::get_data(task) };
+  let inner_rows = data.input.len() / data.inner_size as usize;
+  let segments = data.blocks_per_row as u32;
+
+  workassisting_loop_row_column!(loop_arguments, segments,
+  // Multiple-rows algorithm
+  |block_index| {
+    let rows_per_block = (BLOCK_SIZE as usize / data.inner_size as usize).min(inner_rows);
+    let first_row = block_index as usize * rows_per_block;
+
+    for row in first_row .. (first_row + rows_per_block).min(inner_rows) {
+      let input = &data.input[row * data.inner_size as usize .. (row + 1) * data.inner_size as usize];
+      match data.operation {
+        Operation::Sum => {
+          data.results[row].store(fold_sequential(input), Ordering::Relaxed);
+        },
+        Operation::Histogram(bucket) => {
+          let histogram = histogram_sequential(bucket, input);
+          for (result, count) in data.results[row * BUCKETS .. (row + 1) * BUCKETS].iter().zip(histogram) {
+            result.store(count, Ordering::Relaxed);
+          }
+        }
+      }
+    }
+  },
+  // Row-wise algorithm
+  |block_index| {
+    let row_idx = block_index as usize / data.blocks_per_row as usize;
+    let column_idx = block_index as usize - (row_idx * data.blocks_per_row as usize);
+    reduce_block(data, row_idx, column_idx);
+  },
+  // Column-wise algorithm
+  |block_index, rows_completed| {
+    let new_inner_rows = inner_rows - rows_completed as usize;
+    let row_idx = (block_index as usize % new_inner_rows) + rows_completed as usize;
+    let column_idx = block_index as usize / new_inner_rows;
+    reduce_block(data, row_idx, column_idx);
+  });
+}
+
+fn reduce_block(data: &Data, row_idx: usize, column_idx: usize) {
+  let start = row_idx * data.inner_size as usize + column_idx * BLOCK_SIZE as usize;
+  let end = row_idx * data.inner_size as usize + ((column_idx + 1) * BLOCK_SIZE as usize).min(data.inner_size as usize);
+  let input = &data.input[start .. end];
+
+  match data.operation {
+    Operation::Sum => {
+      data.results[row_idx].fetch_add(fold_sequential(input), Ordering::Relaxed);
+    },
+    Operation::Histogram(bucket) => {
+      let histogram = histogram_sequential(bucket, input);
+      for (result, count) in data.results[row_idx * BUCKETS .. (row_idx + 1) * BUCKETS].iter().zip(histogram) {
+        if count != 0 {
+          result.fetch_add(count, Ordering::Relaxed);
+        }
+      }
+    }
+  }
+}
+
+fn finish(workers: &Workers, task: *mut TaskObject<Data>) {
+  let _ = unsafe { TaskObject::take_data(task) };
+  workers.finish();
+}
@@ -0,0 +1,275 @@
+use core::sync::atomic::{Ordering, AtomicU64};
+use num_format::{Locale, ToFormattedString};
+use crate::cases::scan::{self, parallel_fill, row_column_chained};
+use crate::core::worker::*;
+use crate::utils;
+use crate::utils::array::Multidimensional;
+use crate::utils::benchmark::{benchmark, ChartStyle, Settings};
+
+mod chained;
+mod decode;
+
+// Run-length encoding of every innermost row. A row is encoded as the value of every run and the end of
+// every run: the offset in the row after the last element of the run (the inclusive scan of the run lengths).
+// The runs of a row are written to the start of the row in 'values' and 'run_ends', and the number of runs
+// of every row is stored in 'counts'.
+// Encoding is a chained scan over the number of run heads per block: the prefix of the previous block is
+// the index of the first run that starts in the block. Decoding needs no scan, as the run ends already give
+// the position of every run. Every block of the output finds the run of its first element with a binary search.
+
+// Names of all parallel implementations, as used in the benchmarks
+pub const ALGORITHMS: [&str; 2] = ["Chained RLE encode", "Parallel RLE decode"];
+
+// Lengths of the runs in the benchmarks. They are no divisors of the block size,
+// such that runs cross the boundaries of blocks.
+pub const RUN_LENGTHS: [usize; 2] = [3, 100];
+
+// Bytes moved per element. Encoding reads every element and writes a value and an end per run,
+// decoding reads those per run and writes every element.
+fn bytes_per_element(run_length: usize) -> f64 {
+  8.0 + 16.0 / run_length as f64
+}
+
+pub fn run_rle(settings: &Settings, sizes: &[usize]) { // One-dimensional run-length encoding and decoding
+  for &size in sizes {
+    let input = unsafe { utils::array::MultArray::new([size]) };
+    let values = unsafe { utils::array::MultArray::new([size]) };
+    let run_ends = unsafe { utils::array::MultArray::new([size]) };
+    let output = unsafe { utils::array::MultArray::new([size]) };
+    let counts = [AtomicU64::new(0)];
+    let temp = row_column_chained::create_temp(&input);
+
+    for run_length in RUN_LENGTHS {
+      fill_runs(input.get_data(), run_length);
+      let name = format!("RLE encode, runs of {} (n = {})", run_length, size.to_formatted_string(&Locale::en));
+      benchmark(
+          settings,
+          ChartStyle::WithKey,
+          &name,
+          &[size],
+          bytes_per_element(run_length),
+          || {},
+          || { reference_encode(&input, &values, &run_ends, &counts) }
+        )
+        .parallel("Chained RLE encode", 7, None, true, || {}, |thread_count| {
+          let task = chained::init_slice(input.get_data(), &temp, values.get_data(), run_ends.get_data(), &counts);
+          Workers::run(thread_count, task);
+          compute_output(values.get_data(), run_ends.get_data(), &counts, size)
+        });
+
+      // The encoded input of the decode benchmark
+      reference_encode(&input, &values, &run_ends, &counts);
+      let name = format!("RLE decode, runs of {} (n = {})", run_length, size.to_formatted_string(&Locale::en));
+      benchmark(
+          settings,
+          ChartStyle::WithKey,
+          &name,
+          &[size],
+          bytes_per_element(run_length),
+          || {},
+          || { reference_decode(&values, &run_ends, &counts, &output) }
+        )
+        .parallel("Parallel RLE decode", 7, None, true, || {}, |thread_count| {
+          let task = decode::init_slice(values.get_data(), run_ends.get_data(), &counts, output.get_data());
+          Workers::run(thread_count, task);
+          scan::compute_output(output.get_data())
+        });
+    }
+  }
+}
+
+pub fn run_rle_multidim(settings: &Settings, shapes: &[Vec<usize>]) { // Run-length encoding and decoding of every innermost row
+  for shape in shapes {
+    let input = unsafe { utils::array::DynMultArray::new(shape) };
+    let values = unsafe { utils::array::DynMultArray::new(shape) };
+    let run_ends = unsafe { utils::array::DynMultArray::new(shape) };
+    let output = unsafe { utils::array::DynMultArray::new(shape) };
+    let counts: Box<[AtomicU64]> = (0 .. input.total_inner_count()).map(|_| AtomicU64::new(0)).collect();
+    let temp = row_column_chained::create_temp(&input);
+
+    for run_length in RUN_LENGTHS {
+      fill_runs(input.get_data(), run_length);
+      let name = format!("RLE encode, runs of {} (sh = {:?})", run_length, shape);
+      benchmark(
+          settings,
+          ChartStyle::WithKey,
+          &name,
+          shape,
+          bytes_per_element(run_length),
+          || {},
+          || { reference_encode(&input, &values, &run_ends, &counts) }
+        )
+        .parallel("Chained RLE encode", 7, None, true, || {}, |thread_count| {
+          let task = chained::init_single(&input, &temp, &values, &run_ends, &counts);
+          Workers::run(thread_count, task);
+          compute_output(values.get_data(), run_ends.get_data(), &counts, input.get_inner_size())
+        });
+
+      reference_encode(&input, &values, &run_ends, &counts);
+      let name = format!("RLE decode, runs of {} (sh = {:?})", run_length, shape);
+      benchmark(
+          settings,
+          ChartStyle::WithKey,
+          &name,
+          shape,
+          bytes_per_element(run_length),
+          || {},
+          || { reference_decode(&values, &run_ends, &counts, &output) }
+        )
+        .parallel("Parallel RLE decode", 7, None, true, || {}, |thread_count| {
+          let task = decode::init_single(&values, &run_ends, &counts, &output);
+          Workers::run(thread_count, task);
+          scan::compute_output(output.get_data())
+        });
+    }
+  }
+}
+
+// Verification of the encoding and decoding, see scan::verify. The encoded arrays are compared in full,
+// hence elements after the runs of a row must keep the value of scan::clear. Decoding must give the input.
+pub fn verify(settings: &Settings, shapes: &[Vec<usize>]) -> bool {
+  let mut correct = true;
+  for shape in shapes {
+    let input = unsafe { utils::array::DynMultArray::new(shape) };
+    let values = unsafe { utils::array::DynMultArray::new(shape) };
+    let run_ends = unsafe { utils::array::DynMultArray::new(shape) };
+    let output = unsafe { utils::array::DynMultArray::new(shape) };
+    let counts: Box<[AtomicU64]> = (0 .. input.total_inner_count()).map(|_| AtomicU64::new(0)).collect();
+    let temp = row_column_chained::create_temp(&input);
+    let row_length = input.get_inner_size();
+
+    for run_length in RUN_LENGTHS {
+      fill_runs(input.get_data(), run_length);
+      scan::clear(values.get_data());
+      scan::clear(run_ends.get_data());
+      reference_encode(&input, &values, &run_ends, &counts);
+      let expected_values = scan::snapshot(values.get_data());
+      let expected_run_ends = scan::snapshot(run_ends.get_data());
+      let expected_counts = scan::snapshot(&counts);
+      let expected_output = scan::snapshot(input.get_data());
+
+      println!("Verifying RLE, runs of {} of shape {:?}", run_length, shape);
+      for name in ALGORITHMS {
+        if !settings.includes_algorithm(name) {
+          continue;
+        }
+        for &thread_count in &settings.thread_counts {
+          correct &= match name {
+            "Chained RLE encode" => scan::verify_case(name, thread_count, "out-of-place", || {
+              scan::clear(values.get_data());
+              scan::clear(run_ends.get_data());
+              scan::clear(&counts);
+              Workers::run(thread_count, chained::init_single(&input, &temp, &values, &run_ends, &counts));
+              scan::find_mismatch("counts", &expected_counts, &counts, 1)
+                .or_else(|| scan::find_mismatch("values", &expected_values, values.get_data(), row_length))
+                .or_else(|| scan::find_mismatch("run_ends", &expected_run_ends, run_ends.get_data(), row_length))
+            }),
+            "Parallel RLE decode" => scan::verify_case(name, thread_count, "out-of-place", || {
+              // Decodes the encoding of the sequential reference, independent of the results of the encode
+              reference_encode(&input, &values, &run_ends, &counts);
+              scan::clear(output.get_data());
+              Workers::run(thread_count, decode::init_single(&values, &run_ends, &counts, &output));
+              scan::find_mismatch("output", &expected_output, output.get_data(), row_length)
+            }),
+            _ => panic!("Unknown RLE algorithm {}", name)
+          };
+        }
+      }
+    }
+  }
+  correct
+}
+
+// Sorted values, with runs of 'run_length' elements. Rows of multidimensional arrays continue the
+// values of the previous row, such that runs also cross the boundaries of rows.
+pub fn fill_runs(values: &[AtomicU64], run_length: usize) {
+  parallel_fill::fill_with(values, move |idx| (idx / run_length) as u64);
+}
+
+// Whether the element at 'index' of 'row' is the first element of a run
+#[inline(always)]
+pub fn is_head(row: &[AtomicU64], index: usize) -> bool {
+  index == 0 || row[index].load(Ordering::Relaxed) != row[index - 1].load(Ordering::Relaxed)
+}
+
+// Whether the element at 'index' of 'row' is the last element of a run
+#[inline(always)]
+pub fn is_tail(row: &[AtomicU64], index: usize) -> bool {
+  index == row.len() - 1 || row[index].load(Ordering::Relaxed) != row[index + 1].load(Ordering::Relaxed)
+}
+
+// Encodes the elements start .. end of 'row', given the number of runs that start before 'start'.
+// The runs are written to 'values' and 'run_ends' of the row. Returns the number of runs that start before 'end'.
+pub fn encode_sequential(row: &[AtomicU64], start: usize, end: usize, mut run_count: u64, values: &[AtomicU64], run_ends: &[AtomicU64]) -> u64 {
+  for index in start .. end {
+    if is_head(row, index) {
+      values[run_count as usize].store(row[index].load(Ordering::Relaxed), Ordering::Relaxed);
+      run_count += 1;
+    }
+    if is_tail(row, index) {
+      run_ends[run_count as usize - 1].store(index as u64 + 1, Ordering::Relaxed);
+    }
+  }
+  run_count
+}
+
+pub fn count_heads(row: &[AtomicU64], start: usize, end: usize) -> u64 {
+  let mut count = 0;
+  for index in start .. end {
+    if is_head(row, index) {
+      count += 1;
+    }
+  }
+  count
+}
+
+// Decodes the runs first_run .. of a row to the elements start .. end of 'output' of the row.
+// The run with index 'first_run' must contain the element at 'start'.
+pub fn decode_sequential(values: &[AtomicU64], run_ends: &[AtomicU64], first_run: usize, start: usize, end: usize, output: &[AtomicU64]) {
+  let mut run = first_run;
+  let mut index = start;
+  while index < end {
+    let value = values[run].load(Ordering::Relaxed);
+    let run_end = (run_ends[run].load(Ordering::Relaxed) as usize).min(end);
+    for element in &output[index .. run_end] {
+      element.store(value, Ordering::Relaxed);
+    }
+    index = run_end;
+    run += 1;
+  }
+}
+
+pub fn reference_encode<A: Multidimensional<AtomicU64>>(input: &A, values: &A, run_ends: &A, counts: &[AtomicU64]) -> u64 {
+  let row_length = input.get_inner_size();
+  for (row, count) in counts.iter().enumerate() {
+    let range = row * row_length .. (row + 1) * row_length;
+    let run_count = encode_sequential(&input.get_data()[range.clone()], 0, row_length, 0, &values.get_data()[range.clone()], &run_ends.get_data()[range]);
+    count.store(run_count, Ordering::Relaxed);
+  }
+  compute_output(values.get_data(), run_ends.get_data(), counts, row_length)
+}
+
+pub fn reference_decode<A: Multidimensional<AtomicU64>>(values: &A, run_ends: &A, counts: &[AtomicU64], output: &A) -> u64 {
+  let row_length = values.get_inner_size();
+  for row in 0 .. counts.len() {
+    let range = row * row_length .. (row + 1) * row_length;
+    decode_sequential(&values.get_data()[range.clone()], &run_ends.get_data()[range.clone()], 0, 0, row_length, &output.get_data()[range]);
+  }
+  scan::compute_output(output.get_data())
+}
+
+// Checksum of the number of runs and the first and last run of every row.
+// Elements after the runs of a row are not written, and thus not included.
+pub fn compute_output(values: &[AtomicU64], run_ends: &[AtomicU64], counts: &[AtomicU64], row_length: usize) -> u64 {
+  let mut checksum = 0;
+  for (row, count) in counts.iter().enumerate() {
+    let count = count.load(Ordering::Relaxed) as usize;
+    let row_start = row * row_length;
+    checksum += count as u64;
+    if count > 0 {
+      checksum += values[row_start].load(Ordering::Relaxed) + values[row_start + count - 1].load(Ordering::Relaxed);
+      checksum += 3 * (run_ends[row_start].load(Ordering::Relaxed) + run_ends[row_start + count - 1].load(Ordering::Relaxed));
+    }
+  }
+  checksum
+}
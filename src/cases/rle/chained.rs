@@ -0,0 +1,80 @@
+use core::sync::atomic::{Ordering, AtomicU64};
+use crate::cases::rle::{count_heads, encode_sequential};
+use crate::cases::scan::BLOCK_SIZE;
+use crate::cases::scan::chained_count::chained_count;
+use crate::cases::scan::row_column_chained::{ BlockInfo, reset };
+use crate::core::worker::*;
+use crate::core::task::*;
+use crate::core::workassisting_loop::*;
+use crate::utils::array::Multidimensional;
+
+// Run-length encoding of every innermost row in a single pass, using the chained scan over the number of
+// run heads per block. The prefix of the previous block is the number of runs that start before the block,
+// which is the index of the first run that starts in the block. A run that crosses the start of the block
+// has the index before that, such that the block can also write its end.
+// The number of runs of every row is stored in 'counts'.
+
+struct Data<'a> {
+  input: &'a [AtomicU64],
+  temp: &'a [BlockInfo],
+  values: &'a [AtomicU64],
+  run_ends: &'a [AtomicU64],
+  counts: &'a [AtomicU64],
+  blocks_per_row: u64,
+  inner_size: u64
+}
+
+pub fn init_single<A: Multidimensional<AtomicU64>>(input: &A, temp: &[BlockInfo], values: &A, run_ends: &A, counts: &[AtomicU64]) -> Task {
+  reset(temp);
+  create_task(input.get_data(), input.get_inner_size(), temp, values.get_data(), run_ends.get_data(), counts)
+}
+
+// Encodes a flat slice as a single row. 'counts' contains one element.
+pub fn init_slice(input: &[AtomicU64], temp: &[BlockInfo], values: &[AtomicU64], run_ends: &[AtomicU64], counts: &[AtomicU64]) -> Task {
+  reset(temp);
+  create_task(input, input.len(), temp, values, run_ends, counts)
+}
+
+fn create_task<'a>(input: &'a [AtomicU64], inner_size: usize, temp: &'a [BlockInfo], values: &'a [AtomicU64], run_ends: &'a [AtomicU64], counts: &'a [AtomicU64]) -> Task {
+  let inner_size = inner_size as u64;
+  let inner_rows = (input.len() as u64).checked_div(inner_size).unwrap_or(0);
+  assert_eq!(values.len(), input.len());
+  assert_eq!(run_ends.len(), input.len());
+  assert_eq!(counts.len(), inner_rows as usize);
+
+  let blocks_per_row = inner_size.div_ceil(BLOCK_SIZE);
+  let block_count = blocks_per_row.checked_mul(inner_rows).expect("Block count overflowed u64 size") as u32;
+
+  Task::new_dataparallel::<Data>(run, finish, Data{ input, temp, values, run_ends, counts, blocks_per_row, inner_size }, block_count, false)
+}
+
+fn run(_workers: &Workers, task: *const TaskObject<Data>, loop_arguments: LoopArguments) {
+  let data = unsafe { TaskObject::get_data(task) };
+  let mut sequential = true;
+
+  workassisting_loop!(loop_arguments, |block_index| {
+    let row_idx = block_index as usize / data.blocks_per_row as usize;
+    let column_idx = block_index as usize - (row_idx * data.blocks_per_row as usize);
+
+    let row_range = row_idx * data.inner_size as usize .. (row_idx + 1) * data.inner_size as usize;
+    let row = &data.input[row_range.clone()];
+    let values = &data.values[row_range.clone()];
+    let run_ends = &data.run_ends[row_range];
+    let start = column_idx * BLOCK_SIZE as usize;
+    let end = (start + BLOCK_SIZE as usize).min(row.len());
+
+    let prefix = chained_count(data.temp, block_index as usize, column_idx, &mut sequential,
+      || count_heads(row, start, end),
+      |run_count| encode_sequential(row, start, end, run_count, values, run_ends)
+    );
+
+    if column_idx == data.blocks_per_row as usize - 1 {
+      data.counts[row_idx].store(prefix, Ordering::Relaxed);
+    }
+  });
+}
+
+fn finish(workers: &Workers, task: *mut TaskObject<Data>) {
+  let _ = unsafe { TaskObject::take_data(task) };
+  workers.finish();
+}
@@ -0,0 +1,70 @@
+use core::sync::atomic::{Ordering, AtomicU64};
+use crate::cases::rle::decode_sequential;
+use crate::cases::scan::BLOCK_SIZE;
+use crate::core::worker::*;
+use crate::core::task::*;
+use crate::core::workassisting_loop::*;
+use crate::utils::array::Multidimensional;
+use crate::utils::numa::NodeClaims;
+
+// Run-length decoding of every innermost row. The output is divided in blocks of BLOCK_SIZE elements per row.
+// As the run ends are sorted, every block finds the run of its first element with a binary search,
+// after which it can expand the runs independently of the other blocks. Hence workers prefer the blocks on their own NUMA node.
+
+struct Data<'a> {
+  values: &'a [AtomicU64],
+  run_ends: &'a [AtomicU64],
+  counts: &'a [AtomicU64],
+  output: &'a [AtomicU64],
+  blocks_per_row: u64,
+  inner_size: u64,
+  claims: NodeClaims
+}
+
+pub fn init_single<A: Multidimensional<AtomicU64>>(values: &A, run_ends: &A, counts: &[AtomicU64], output: &A) -> Task {
+  create_task(values.get_data(), run_ends.get_data(), counts, output.get_data(), output.get_inner_size())
+}
+
+// Decodes a single row to a flat slice. 'counts' contains one element.
+pub fn init_slice(values: &[AtomicU64], run_ends: &[AtomicU64], counts: &[AtomicU64], output: &[AtomicU64]) -> Task {
+  create_task(values, run_ends, counts, output, output.len())
+}
+
+fn create_task<'a>(values: &'a [AtomicU64], run_ends: &'a [AtomicU64], counts: &'a [AtomicU64], output: &'a [AtomicU64], inner_size: usize) -> Task {
+  let inner_size = inner_size as u64;
+  let inner_rows = (output.len() as u64).checked_div(inner_size).unwrap_or(0);
+  assert_eq!(values.len(), output.len());
+  assert_eq!(run_ends.len(), output.len());
+  assert_eq!(counts.len(), inner_rows as usize);
+
+  let blocks_per_row = inner_size.div_ceil(BLOCK_SIZE);
+  let block_count = blocks_per_row.checked_mul(inner_rows).expect("Block count overflowed u64 size") as u32;
+
+  Task::new_dataparallel::<Data>(run, finish, Data{ values, run_ends, counts, output, blocks_per_row, inner_size, claims: NodeClaims::new(block_count) }, block_count, false)
+}
+
+fn run(_workers: &Workers, task: *const TaskObject<Data>, loop_arguments: LoopArguments) {
+  let data = unsafe { TaskObject::get_data(task) };
+
+  workassisting_loop_numa!(loop_arguments, &data.claims, |block_index| {
+    let row_idx = block_index as usize / data.blocks_per_row as usize;
+    let column_idx = block_index as usize - (row_idx * data.blocks_per_row as usize);
+
+    let row_range = row_idx * data.inner_size as usize .. (row_idx + 1) * data.inner_size as usize;
+    let run_count = data.counts[row_idx].load(Ordering::Relaxed) as usize;
+    let values = &data.values[row_range.clone()];
+    let run_ends = &data.run_ends[row_range.clone()];
+    let output = &data.output[row_range];
+    let start = column_idx * BLOCK_SIZE as usize;
+    let end = (start + BLOCK_SIZE as usize).min(output.len());
+
+    // The first run that ends after the start of the block contains the start of the block
+    let first_run = run_ends[.. run_count].partition_point(|run_end| run_end.load(Ordering::Relaxed) as usize <= start);
+    decode_sequential(values, run_ends, first_run, start, end, output);
+  });
+}
+
+fn finish(workers: &Workers, task: *mut TaskObject<Data>) {
+  let _ = unsafe { TaskObject::take_data(task) };
+  workers.finish();
+}
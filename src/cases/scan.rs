@@ -1,28 +1,48 @@
 use core::sync::atomic::{Ordering, AtomicU64};
 use num_format::{Locale, ToFormattedString};
 use crate::core::worker::*;
+use crate::core::task::Task;
 use crate::utils;
-use crate::utils::benchmark::{benchmark, ChartStyle};
+use crate::utils::benchmark::{benchmark, ChartStyle, Settings};
 
 mod zero_overhead;
-mod row_column_chained;
+pub mod row_column_chained;
 mod sequential_rowbased;
 mod rowwise_chained;
-mod columnwise_chained;
+pub mod columnwise_chained;
+mod reduce_then_scan;
+mod scan_then_propagate;
+pub mod dispatch;
+pub mod lookback_statistics;
+pub mod parallel_fill;
+pub mod chunked;
+pub mod chained_count;
+
+// Names of all parallel implementations, as used in the benchmarks
+pub const ALGORITHMS: [&str; 8] = ["Adaptive chained", "Assisting column-wise chained", "Sequential row-based", "Column-wise chained", "Row-wise chained", "Reduce-then-scan", "Scan-then-propagate", "Automatic dispatch"];
+// Parallel C++ reference implementations, which are only available if the C++ reference is enabled
+pub const CPP_ALGORITHMS: [&str; 3] = ["TBB parallel_scan", "OpenMP scan", "std::inclusive_scan par"];
 
 // Const block_size used during scanning
 pub const BLOCK_SIZE:u64 = 1024 * 4;
 // One dimensional input size used for the one-dim prefix sum/inplace prefix sum
 pub const ONEDIM_SIZE: usize = 1024 * 1024 * 64;
-// Input shapes used for the multidimensional prefix sum/inplace prefix sum
-pub const MULTIDIM_SHAPES:[[usize;2];4] = [[10000, 10000], [4000, 25000], [4, 25000000], [100000, 1000]];
+// Bytes moved per element by the out-of-place and in-place scans, which read and write every element once
+pub const BYTES_PER_ELEMENT: f64 = 16.0;
+// Default input shapes used for the multidimensional prefix sum/inplace prefix sum
+pub const MULTIDIM_SHAPES: &[&[usize]] = &[&[10000, 10000], &[4000, 25000], &[4, 25000000], &[100000, 1000]];
+// Default input shape used for the comparison between row-wise and column-wise scanning
+pub const ROW_VS_COLUMN_SHAPES: &[&[usize]] = &[&[10000, 10000]];
 // Higher dimensional input, in comparison to the original input: [10000, 10000]
-pub const HIGHERDIM_SHAPE:[[usize;3];1] = [[100, 100, 10000]];
+pub const HIGHERDIM_SHAPE: &[&[usize]] = &[&[100, 100, 10000]];
 // Four-dimensional input
-pub const FOURDIM_SHAPE:[[usize;4];1] = [[100, 100, 100, 100]];
+pub const FOURDIM_SHAPE: &[&[usize]] = &[&[100, 100, 100, 100]];
+// Shapes used in the verification mode, covering rows shorter and longer than a block, partial blocks, single elements,
+// and a few long rows on which the row-wise and column-wise scheduling of the assisting scans meet
+pub const VERIFY_SHAPES: &[&[usize]] = &[&[1], &[4095], &[4097], &[100000], &[1000, 1], &[1000, 3], &[17, 2049], &[3, 20497], &[5, 7, 9000], &[100, 100, 100], &[3, 200497], &[5, 170001]];
 
-pub fn run(cpp_enabled: bool) { // One-dimensional prefix sum
-  for size in [ONEDIM_SIZE] {
+pub fn run(settings: &Settings, sizes: &[usize]) { // One-dimensional prefix sum
+  for &size in sizes {
     let input = unsafe { utils::array::MultArray::new([size]) };
     let output = unsafe { utils::array::MultArray::new([size]) };
     let temp = row_column_chained::create_temp(&input);
@@ -30,197 +50,365 @@ pub fn run(cpp_enabled: bool) { // One-dimensional prefix sum
 
     let name = "Prefix-sum (n = ".to_owned() + &(size).to_formatted_string(&Locale::en) + ")";
     benchmark(
+        settings,
         ChartStyle::WithKey,
         &name,
+        &[size],
+        BYTES_PER_ELEMENT,
         || {},
         || { reference_sequential_single(&input.get_data(), &output.get_data()) }
       )
       .parallel("Adaptive chained", 7, Some(13), false, || {}, |thread_count| {
         let task = zero_overhead::init_single(&input.get_data(), &temp, &output.get_data());
         Workers::run(thread_count, task);
-        compute_output(&output.get_data())
+        compute_output(output.get_data())
       })
       .parallel("Assisting column-wise chained", 6, None, true, || {}, |thread_count| {
         let task = row_column_chained::init_single(&input, &temp, &output);
         Workers::run(thread_count, task);
-        compute_output(&output.get_data())
+        compute_output(output.get_data())
+      })
+      .parallel("Reduce-then-scan", 3, None, false, || {}, |thread_count| {
+        let task = reduce_then_scan::init_single(&input, &temp, &output);
+        Workers::run(thread_count, task);
+        compute_output(output.get_data())
       })
-      .cpp_sequential(cpp_enabled, "Reference sequential C++", "scan-sequential", size, size, 1);
+      .parallel("Scan-then-propagate", 4, None, false, || {}, |thread_count| {
+        let task = scan_then_propagate::init_single(&input, &temp, &output);
+        Workers::run(thread_count, task);
+        compute_output(output.get_data())
+      })
+      .cpp_sequential("Reference sequential C++", "scan-sequential", size, size, 1)
+      .cpp_parallel(CPP_ALGORITHMS[0], 2, None, "scan-tbb")
+      .cpp_parallel(CPP_ALGORITHMS[1], 2, Some(12), "scan-openmp")
+      .cpp_parallel(CPP_ALGORITHMS[2], 2, Some(14), "scan-std-par");
   }
 }
 
-pub fn run_multidim(cpp_enabled: bool) { // Multidimensional prefix sum
-  for shape in MULTIDIM_SHAPES {
-    let input = unsafe { utils::array::MultArray::new(shape) };
-    let output = unsafe { utils::array::MultArray::new(shape) };
+pub fn run_multidim(settings: &Settings, shapes: &[Vec<usize>]) { // Multidimensional prefix sum
+  let dispatch_table = dispatch::DecisionTable::load_or_default(dispatch::TABLE_PATH);
+  for shape in shapes {
+    let input = unsafe { utils::array::DynMultArray::new(shape) };
+    let output = unsafe { utils::array::DynMultArray::new(shape) };
     let temp = row_column_chained::create_temp(&input);
     fill(input.get_data());
 
     let name = "Prefix-sum (sh = ".to_owned() + &format!("{:?}", shape) + ")"; //
     benchmark(
+        settings,
         ChartStyle::WithKey,
         &name,
+        shape,
+        BYTES_PER_ELEMENT,
         || {},
         || { reference_sequential_multidim(&input.get_data(), &output.get_data(), input.get_inner_size(), input.total_inner_count()) }
       )
       .parallel("Sequential row-based", 5, None, false, || {}, |thread_count| {
         let task = sequential_rowbased::create_task(&input, &output);
         Workers::run(thread_count, task);
-        compute_output(&output.get_data())
+        compute_output(output.get_data())
       })
       .parallel("Column-wise chained", 7, None, true, || {}, |thread_count| {
         let task = columnwise_chained::init_single(&input, &temp, &output);
         Workers::run(thread_count, task);
-        compute_output(&output.get_data())
+        compute_output(output.get_data())
       })
       .parallel("Row-wise chained", 8, None, true, || {}, |thread_count| {
         let task = rowwise_chained::init_single(&input, &temp, &output);
         Workers::run(thread_count, task);
-        compute_output(&output.get_data())
+        compute_output(output.get_data())
       })
       .parallel("Assisting column-wise chained", 6, None, true, || {}, |thread_count| {
         let task = row_column_chained::init_single(&input, &temp, &output);
         Workers::run(thread_count, task);
-        compute_output(&output.get_data())
+        compute_output(output.get_data())
       })
-      .cpp_sequential(cpp_enabled, "Reference sequential C++", "scan-multidim-sequential", input.get_data().len(), input.get_inner_size(), input.total_inner_count());
+      .parallel("Reduce-then-scan", 3, None, false, || {}, |thread_count| {
+        let task = reduce_then_scan::init_single(&input, &temp, &output);
+        Workers::run(thread_count, task);
+        compute_output(output.get_data())
+      })
+      .parallel("Scan-then-propagate", 4, None, false, || {}, |thread_count| {
+        let task = scan_then_propagate::init_single(&input, &temp, &output);
+        Workers::run(thread_count, task);
+        compute_output(output.get_data())
+      })
+      .parallel("Automatic dispatch", 1, None, true, || {}, |thread_count| {
+        let task = dispatch::init_single(&dispatch_table, thread_count, &input, &temp, &output);
+        Workers::run(thread_count, task);
+        compute_output(output.get_data())
+      })
+      .cpp_sequential("Reference sequential C++", "scan-multidim-sequential", input.get_data().len(), input.get_inner_size(), input.total_inner_count())
+      .cpp_parallel(CPP_ALGORITHMS[0], 2, None, "scan-multidim-tbb")
+      .cpp_parallel(CPP_ALGORITHMS[1], 2, Some(12), "scan-multidim-openmp")
+      .cpp_parallel(CPP_ALGORITHMS[2], 2, Some(14), "scan-multidim-std-par");
   }
 }
 
-pub fn run_inplace(cpp_enabled: bool) { // One-dimensional in-place prefix sum
-  for size in [ONEDIM_SIZE] {
+pub fn run_inplace(settings: &Settings, sizes: &[usize]) { // One-dimensional in-place prefix sum
+  for &size in sizes {
     let values = unsafe { utils::array::MultArray::new([size]) };
     let temp = row_column_chained::create_temp(&values);
 
     let name = "In-place prefix-sum (n = ".to_owned() + &(size).to_formatted_string(&Locale::en) + ")";
     benchmark(
+        settings,
         ChartStyle::WithKey,
         &name,
+        &[size],
+        BYTES_PER_ELEMENT,
         || { fill(&values.get_data()) },
         || { reference_sequential_single(&values.get_data(), &values.get_data()) }
       )
       .parallel("Adaptive chained", 7, Some(13), false, || { fill(&values.get_data()) }, |thread_count| {
         let task = zero_overhead::init_single(&values.get_data(), &temp, &values.get_data());
         Workers::run(thread_count, task);
-        compute_output(&values.get_data())
+        compute_output(values.get_data())
       })
       .parallel("Assisting column-wise chained", 6, None, true, || { fill(&values.get_data()) }, |thread_count| {
         let task = row_column_chained::init_single(&values, &temp, &values);
         Workers::run(thread_count, task);
-        compute_output(&values.get_data())
+        compute_output(values.get_data())
       })
-      .cpp_sequential(cpp_enabled, "Reference sequential C++", "scan-inplace-sequential", size, size, 1);
+      .cpp_sequential("Reference sequential C++", "scan-inplace-sequential", size, size, 1)
+      .cpp_parallel(CPP_ALGORITHMS[0], 2, None, "scan-inplace-tbb")
+      .cpp_parallel(CPP_ALGORITHMS[1], 2, Some(12), "scan-inplace-openmp")
+      .cpp_parallel(CPP_ALGORITHMS[2], 2, Some(14), "scan-inplace-std-par");
   }
 }
 
-pub fn run_inplace_multidim(cpp_enabled: bool) { // Multidimensional in-place prefix sum
-  for shape in MULTIDIM_SHAPES {
-    let values = unsafe { utils::array::MultArray::new(shape) };
+pub fn run_inplace_multidim(settings: &Settings, shapes: &[Vec<usize>]) { // Multidimensional in-place prefix sum
+  for shape in shapes {
+    let values = unsafe { utils::array::DynMultArray::new(shape) };
     let temp = row_column_chained::create_temp(&values);
   
     let name = "In-place prefix-sum (sh = ".to_owned() + &format!("{:?}", shape) + ")";
     benchmark(
+        settings,
         ChartStyle::WithKey,
         &name,
+        shape,
+        BYTES_PER_ELEMENT,
         || { fill(&values.get_data()) },
         || { reference_sequential_multidim(&values.get_data(), &values.get_data(), values.get_inner_size(), values.total_inner_count()) }
       )
       .parallel("Sequential row-based", 5, None, false, || { fill(&values.get_data()) }, |thread_count| {
         let task = sequential_rowbased::create_task(&values, &values);
         Workers::run(thread_count, task);
-        compute_output(&values.get_data())
+        compute_output(values.get_data())
       })
       .parallel("Column-wise chained", 7, None, true, || { fill(&values.get_data()) }, |thread_count| {
         let task = columnwise_chained::init_single(&values, &temp, &values);
         Workers::run(thread_count, task);
-        compute_output(&values.get_data())
+        compute_output(values.get_data())
       })
       .parallel("Row-wise chained", 8, None, true, || { fill(&values.get_data()) }, |thread_count| {
         let task = rowwise_chained::init_single(&values, &temp, &values);
         Workers::run(thread_count, task);
-        compute_output(&values.get_data())
+        compute_output(values.get_data())
       })
       .parallel("Assisting column-wise chained", 6, None, true, || { fill(&values.get_data()) }, |thread_count| {
         let task = row_column_chained::init_single(&values, &temp, &values);
         Workers::run(thread_count, task);
-        compute_output(&values.get_data())
+        compute_output(values.get_data())
       })
-      .cpp_sequential(cpp_enabled, "Reference sequential C++", "scan-inplace-multidim-sequential", values.get_data().len(), values.get_inner_size(), values.total_inner_count());
+      .cpp_sequential("Reference sequential C++", "scan-inplace-multidim-sequential", values.get_data().len(), values.get_inner_size(), values.total_inner_count())
+      .cpp_parallel(CPP_ALGORITHMS[0], 2, None, "scan-inplace-multidim-tbb")
+      .cpp_parallel(CPP_ALGORITHMS[1], 2, Some(12), "scan-inplace-multidim-openmp")
+      .cpp_parallel(CPP_ALGORITHMS[2], 2, Some(14), "scan-inplace-multidim-std-par");
   }
 }
 
-pub fn run_rowwise_vs_columnwise() { // Prefix sum comparison between row-wise and column-wise scanning
-  for shape in [[10000, 10000]] {
-    let input = unsafe { utils::array::MultArray::new(shape) };
-    let output = unsafe { utils::array::MultArray::new(shape) };
+pub fn run_rowwise_vs_columnwise(settings: &Settings, shapes: &[Vec<usize>]) { // Prefix sum comparison between row-wise and column-wise scanning
+  for shape in shapes {
+    let input = unsafe { utils::array::DynMultArray::new(shape) };
+    let output = unsafe { utils::array::DynMultArray::new(shape) };
     let temp = row_column_chained::create_temp(&input);
     fill(input.get_data());
 
     let name = "Row vs Column (sh = ".to_owned() + &format!("{:?}", shape) + ")"; //
     benchmark(
+        settings,
         ChartStyle::WithKey,
         &name,
+        shape,
+        BYTES_PER_ELEMENT,
         || {},
         || { reference_sequential_multidim(&input.get_data(), &output.get_data(), input.get_inner_size(), input.total_inner_count()) }
       )
       .parallel("Column-wise chained", 7, None, true, || {}, |thread_count| {
         let task = columnwise_chained::init_single(&input, &temp, &output);
         Workers::run(thread_count, task);
-        compute_output(&output.get_data())
+        compute_output(output.get_data())
       })
       .parallel("Row-wise chained", 8, None, true, || {}, |thread_count| {
         let task = rowwise_chained::init_single(&input, &temp, &output);
         Workers::run(thread_count, task);
-        compute_output(&output.get_data())
+        compute_output(output.get_data())
       });
   }
 }
 
-pub fn run_custom_shape<const N: usize, const M:usize>(shapes: [[usize; N]; M]) { // Multidimensional prefix sum, to show that the performance is indepent of the number of dimensions
+pub fn run_custom_shape(settings: &Settings, shapes: &[Vec<usize>]) { // Multidimensional prefix sum, to show that the performance is indepent of the number of dimensions
   for shape in shapes {
-    let input = unsafe { utils::array::MultArray::new(shape) };
-    let output = unsafe { utils::array::MultArray::new(shape) };
+    let input = unsafe { utils::array::DynMultArray::new(shape) };
+    let output = unsafe { utils::array::DynMultArray::new(shape) };
     let temp = row_column_chained::create_temp(&input);
     fill(input.get_data());
 
     let name = "Prefix-sum (sh = ".to_owned() + &format!("{:?}", shape) + ")"; //
     benchmark(
+        settings,
         ChartStyle::WithoutKey,
         &name,
+        shape,
+        BYTES_PER_ELEMENT,
         || {},
         || { reference_sequential_multidim(&input.get_data(), &output.get_data(), input.get_inner_size(), input.total_inner_count()) }
       )
       .parallel("Sequential row-based", 5, None, false, || {}, |thread_count| {
         let task = sequential_rowbased::create_task(&input, &output);
         Workers::run(thread_count, task);
-        compute_output(&output.get_data())
+        compute_output(output.get_data())
       })
       .parallel("Column-wise chained", 7, None, true, || {}, |thread_count| {
         let task = columnwise_chained::init_single(&input, &temp, &output);
         Workers::run(thread_count, task);
-        compute_output(&output.get_data())
+        compute_output(output.get_data())
       })
       .parallel("Row-wise chained", 8, None, true, || {}, |thread_count| {
         let task = rowwise_chained::init_single(&input, &temp, &output);
         Workers::run(thread_count, task);
-        compute_output(&output.get_data())
+        compute_output(output.get_data())
       })
       .parallel("Assisting column-wise chained", 6, None, true, || {}, |thread_count| {
         let task = row_column_chained::init_single(&input, &temp, &output);
         Workers::run(thread_count, task);
-        compute_output(&output.get_data())
+        compute_output(output.get_data())
       });
   }
 }
 
-pub fn fill(values: &[AtomicU64]) {
-  for (idx, value) in values.iter().enumerate() {
-    value.store(random(idx as u64) as u64, Ordering::Relaxed);
+// Verification mode: runs every parallel implementation VERIFY_RUNS times per thread count, out-of-place and in-place,
+// and compares the full output element-by-element against the sequential reference.
+// Returns false if any implementation produced a wrong result.
+pub fn verify(settings: &Settings, shapes: &[Vec<usize>]) -> bool {
+  let dispatch_table = dispatch::DecisionTable::load_or_default(dispatch::TABLE_PATH);
+  let mut correct = true;
+  for shape in shapes {
+    let input = unsafe { utils::array::DynMultArray::new(shape) };
+    let output = unsafe { utils::array::DynMultArray::new(shape) };
+    let temp = row_column_chained::create_temp(&input);
+    fill(input.get_data());
+    reference_sequential_multidim(input.get_data(), output.get_data(), input.get_inner_size(), input.total_inner_count());
+    let expected = snapshot(output.get_data());
+
+    println!("Verifying the scans of shape {:?}", shape);
+    for name in ALGORITHMS {
+      // The adaptive chained scan only supports one-dimensional inputs
+      if !settings.includes_algorithm(name) || (name == "Adaptive chained" && shape.len() != 1) {
+        continue;
+      }
+      for &thread_count in &settings.thread_counts {
+        for inplace in [false, true] {
+          let target = if inplace { &input } else { &output };
+          let mode = if inplace { "in-place" } else { "out-of-place" };
+          correct &= verify_case(name, thread_count, mode, || {
+            // The in-place runs overwrite the input
+            fill(input.get_data());
+            clear(output.get_data());
+            let task = create_verify_task(name, &dispatch_table, thread_count, &input, &temp, target);
+            Workers::run(thread_count, task);
+            find_mismatch("output", &expected, target.get_data(), input.get_inner_size())
+          });
+        }
+      }
+    }
   }
+  correct
 }
 
+// Number of runs of every case of the verification, as races between the workers may only show up in some runs
+pub const VERIFY_RUNS: usize = 5;
+
+// The first element of an output that differs from the reference
+pub struct Mismatch {
+  pub array: &'static str,
+  pub index: usize,
+  pub row_length: usize,
+  pub expected: u64,
+  pub actual: u64
+}
+
+// Runs a case of the verification VERIFY_RUNS times, and prints whether the outputs were correct.
+// 'run' runs the algorithm once, and compares its output against the reference.
+pub fn verify_case<F: FnMut() -> Option<Mismatch>>(name: &str, thread_count: usize, mode: &str, mut run: F) -> bool {
+  for run_index in 0 .. VERIFY_RUNS {
+    if let Some(mismatch) = run() {
+      let row = mismatch.index / mismatch.row_length.max(1);
+      let column = mismatch.index - row * mismatch.row_length;
+      println!(
+        "  {:32} {:02} threads {:12} MISMATCH in run {} in the {} at index {} (row {}, column {}, block {} of the row): expected {}, got {}",
+        name, thread_count, mode, run_index + 1, mismatch.array, mismatch.index, row, column, column / BLOCK_SIZE as usize, mismatch.expected, mismatch.actual
+      );
+      return false;
+    }
+  }
+  println!("  {:32} {:02} threads {:12} ok", name, thread_count, mode);
+  true
+}
+
+// Copy of an output, as reference for the verification
+pub fn snapshot(values: &[AtomicU64]) -> Vec<u64> {
+  values.iter().map(|value| value.load(Ordering::Relaxed)).collect()
+}
+
+// Overwrites an output, such that elements that are not written are detected
+pub fn clear(values: &[AtomicU64]) {
+  for value in values {
+    value.store(u64::MAX, Ordering::Relaxed);
+  }
+}
+
+// Returns the first element of 'actual' that differs from 'expected'. 'row_length' is only used to report the row.
+pub fn find_mismatch(array: &'static str, expected: &[u64], actual: &[AtomicU64], row_length: usize) -> Option<Mismatch> {
+  find_mismatch_by(array, expected, actual, row_length, |expected, actual| expected == actual)
+}
+
+// Like find_mismatch, comparing the elements with 'equal'
+pub fn find_mismatch_by<Equal: Fn(u64, u64) -> bool>(array: &'static str, expected: &[u64], actual: &[AtomicU64], row_length: usize, equal: Equal) -> Option<Mismatch> {
+  assert_eq!(expected.len(), actual.len());
+  expected.iter().zip(actual).position(|(&expected, actual)| !equal(expected, actual.load(Ordering::Relaxed)))
+    .map(|index| Mismatch{ array, index, row_length, expected: expected[index], actual: actual[index].load(Ordering::Relaxed) })
+}
+
+fn create_verify_task<A: utils::array::Multidimensional<AtomicU64>>(name: &str, dispatch_table: &dispatch::DecisionTable, thread_count: usize, input: &A, temp: &[row_column_chained::BlockInfo], output: &A) -> Task {
+  match name {
+    "Adaptive chained" => zero_overhead::init_single(input.get_data(), temp, output.get_data()),
+    "Assisting column-wise chained" => row_column_chained::init_single(input, temp, output),
+    "Sequential row-based" => sequential_rowbased::create_task(input, output),
+    "Column-wise chained" => columnwise_chained::init_single(input, temp, output),
+    "Row-wise chained" => rowwise_chained::init_single(input, temp, output),
+    "Reduce-then-scan" => reduce_then_scan::init_single(input, temp, output),
+    "Scan-then-propagate" => scan_then_propagate::init_single(input, temp, output),
+    "Automatic dispatch" => dispatch::init_single(dispatch_table, thread_count, input, temp, output),
+    _ => panic!("Unknown algorithm {}", name)
+  }
+}
+
+// Fills the input with pseudo-random values, in parallel. See parallel_fill.
+pub fn fill(values: &[AtomicU64]) {
+  parallel_fill::fill_with(values, |idx| random(idx as u64) as u64);
+}
+
+// Cheap checksum of a few elements, to detect wrong results during the benchmarks.
+// Use verify for a full comparison against the reference.
 pub fn compute_output(output: &[AtomicU64]) -> u64 {
-  output[1].load(Ordering::Relaxed) + output[98238].load(Ordering::Relaxed) + output[output.len() - 123].load(Ordering::Relaxed) + output[output.len() - 1].load(Ordering::Relaxed)
+  if output.is_empty() {
+    return 0;
+  }
+  let last = output.len() - 1;
+  output[1.min(last)].load(Ordering::Relaxed) + output[98238.min(last)].load(Ordering::Relaxed) + output[output.len().saturating_sub(123)].load(Ordering::Relaxed) + output[last].load(Ordering::Relaxed)
 }
 
 pub fn reference_sequential_single(input: &[AtomicU64], output: &[AtomicU64]) -> u64 {
@@ -253,7 +441,7 @@ pub fn fold_sequential(array: &[AtomicU64]) -> u64 {
   accumulator
 }
 
-fn random(mut seed: u64) -> u32 {
+pub fn random(mut seed: u64) -> u32 {
   seed ^= seed << 13;
   seed ^= seed >> 17;
   seed ^= seed << 5;
@@ -0,0 +1,71 @@
+use core::sync::atomic::Ordering;
+use crate::cases::scan::row_column_chained::{ BlockInfo, STATE_PREFIX_AVAILABLE, STATE_AGGREGATE_AVAILABLE };
+use crate::core::trace;
+
+// Single-pass chained scan over the number of elements that every block writes, shared by the compaction,
+// the partition and the run-length encoding. The prefix of a block is the number of elements written in its row
+// up to and including that block, hence the prefix of the previous block is the offset where the block starts writing.
+// If the previous block already has its prefix, or the block starts a row, the block is written directly.
+// Otherwise the block first counts its elements and shares that count as its aggregate, and then looks back
+// over its predecessors until it finds a prefix. Once a thread had to look back, it keeps doing so until it
+// reaches the next row, as it is then likely working ahead of the other threads.
+
+// Processes the block with descriptor 'block_index' at column 'column_idx' of its row. 'count' gives the number
+// of elements of the block, 'write' writes the block starting at the given offset and returns the offset after the block.
+// 'sequential' tracks whether the thread can still write blocks directly, and must start as true.
+// Returns the prefix of the block.
+pub fn chained_count<Count: FnOnce() -> u64, Write: FnOnce(u64) -> u64>(temp: &[BlockInfo], block_index: usize, column_idx: usize, sequential: &mut bool, count: Count, write: Write) -> u64 {
+  let offset_start = if column_idx == 0 {
+    *sequential = true;
+    Some(0)
+  } else if !*sequential {
+    None // Don't switch back from parallel mode to sequential mode
+  } else {
+    let previous = block_index - 1;
+    let previous_state = temp[previous].state.load(Ordering::Acquire);
+    if previous_state == STATE_PREFIX_AVAILABLE {
+      Some(temp[previous].prefix.load(Ordering::Acquire))
+    } else {
+      None
+    }
+  };
+
+  if let Some(offset) = offset_start {
+    let prefix = write(offset);
+    temp[block_index].prefix.store(prefix, Ordering::Relaxed);
+    temp[block_index].state.store(STATE_PREFIX_AVAILABLE, Ordering::Release);
+    prefix
+  } else {
+    *sequential = false;
+    let local = count();
+    // Share own local value
+    temp[block_index].aggregate.store(local, Ordering::Relaxed);
+    temp[block_index].state.store(STATE_AGGREGATE_AVAILABLE, Ordering::Release);
+
+    // Find the offset of this block
+    let mut offset = 0;
+    let mut previous = block_index - 1;
+
+    let lookback_span = trace::start();
+    loop {
+      let previous_state = temp[previous].state.load(Ordering::Acquire);
+      if previous_state == STATE_PREFIX_AVAILABLE {
+        offset += temp[previous].prefix.load(Ordering::Acquire);
+        break;
+      } else if previous_state == STATE_AGGREGATE_AVAILABLE {
+        offset += temp[previous].aggregate.load(Ordering::Acquire);
+        previous -= 1;
+      } else {
+        // Continue looping until the state of previous block changes.
+      }
+    }
+    trace::complete(lookback_span, "look-back", trace::NO_ARGS);
+
+    // Make prefix available
+    temp[block_index].prefix.store(offset + local, Ordering::Relaxed);
+    temp[block_index].state.store(STATE_PREFIX_AVAILABLE, Ordering::Release);
+
+    write(offset);
+    offset + local
+  }
+}
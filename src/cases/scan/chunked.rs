@@ -0,0 +1,95 @@
+use core::sync::atomic::{Ordering, AtomicU64};
+use crate::cases::scan::BLOCK_SIZE;
+use crate::cases::scan::row_column_chained::{self, BlockInfo};
+use crate::core::worker::*;
+use crate::utils::array::{alloc_undef_u64_array, Buffer, DynMultArray, Multidimensional};
+use crate::utils::mapped_file::Access;
+
+// Scans of arrays that exceed the work size of a single task, for instance arrays mapped from files.
+// The assisting column-wise chained scan supports fewer than 1 << 15 blocks per task, hence larger arrays
+// are scanned in chunks of at most MAX_CHUNK_BLOCKS blocks, with a separate task per chunk.
+// Chunks consist of whole rows if a row fits in a chunk. Longer rows are split in segments,
+// and the scan of a segment continues from the last value of the previous segment.
+// Only the chunk that is being scanned needs to be resident in memory.
+
+pub const MAX_CHUNK_BLOCKS: u64 = (1 << 15) - 1;
+
+// A chunk of an array, of one or more rows of 'inner_size' elements
+struct Chunk<'a> {
+  data: &'a [AtomicU64],
+  inner_size: usize
+}
+
+impl Multidimensional<AtomicU64> for Chunk<'_> {
+  fn get_data(&self) -> &[AtomicU64] {
+    self.data
+  }
+
+  fn get_inner_size(&self) -> usize {
+    self.inner_size
+  }
+
+  fn total_inner_count(&self) -> usize {
+    self.data.len() / self.inner_size
+  }
+}
+
+// Scans every innermost row of 'input' to 'output', which may be the same array
+pub fn scan<A: Multidimensional<AtomicU64>>(thread_count: usize, input_m: &A, output_m: &A) {
+  let inner_size = input_m.get_inner_size();
+  let input = input_m.get_data();
+  let output = output_m.get_data();
+  assert_eq!(input.len(), output.len());
+  if input.is_empty() {
+    return;
+  }
+  // Shared by all chunks, as the scan of a chunk resets it
+  let temp: Buffer<BlockInfo> = unsafe { alloc_undef_u64_array(MAX_CHUNK_BLOCKS as usize) };
+
+  let blocks_per_row = (inner_size as u64).div_ceil(BLOCK_SIZE);
+  if blocks_per_row > MAX_CHUNK_BLOCKS {
+    let segment_size = (MAX_CHUNK_BLOCKS * BLOCK_SIZE) as usize;
+    for (input_row, output_row) in input.chunks(inner_size).zip(output.chunks(inner_size)) {
+      let mut carry = 0;
+      for (input_segment, output_segment) in input_row.chunks(segment_size).zip(output_row.chunks(segment_size)) {
+        scan_chunk(thread_count, input_segment, output_segment, input_segment.len(), &temp, carry);
+        carry = output_segment[output_segment.len() - 1].load(Ordering::Relaxed);
+      }
+    }
+  } else {
+    // Rows of at most one block are combined in blocks of multiple rows (see row_column_chained)
+    let rows_per_chunk = if blocks_per_row > 1 {
+      MAX_CHUNK_BLOCKS / blocks_per_row
+    } else {
+      MAX_CHUNK_BLOCKS * (BLOCK_SIZE / inner_size as u64)
+    };
+    let chunk_size = rows_per_chunk as usize * inner_size;
+    for (input_chunk, output_chunk) in input.chunks(chunk_size).zip(output.chunks(chunk_size)) {
+      scan_chunk(thread_count, input_chunk, output_chunk, inner_size, &temp, 0);
+    }
+  }
+}
+
+fn scan_chunk(thread_count: usize, input: &[AtomicU64], output: &[AtomicU64], inner_size: usize, temp: &[BlockInfo], carry: u64) {
+  let input = Chunk{ data: input, inner_size };
+  let output = Chunk{ data: output, inner_size };
+  Workers::run(thread_count, row_column_chained::init_carried(&input, temp, &output, carry));
+}
+
+// Scans the array of the given shape in the file at 'input_path'. The result is written to the file at
+// 'output_path', which is created if needed, or in place if no output file is given.
+pub fn scan_file(thread_count: usize, shape: &[usize], input_path: &str, output_path: Option<&str>) -> Result<(), String> {
+  match output_path {
+    Some(output_path) => {
+      let input = DynMultArray::<AtomicU64>::open(input_path, shape, Access::ReadOnly)?;
+      let output = DynMultArray::<AtomicU64>::open(output_path, shape, Access::Create)?;
+      scan(thread_count, &input, &output);
+      output.sync()
+    },
+    None => {
+      let values = DynMultArray::<AtomicU64>::open(input_path, shape, Access::ReadWrite)?;
+      scan(thread_count, &values, &values);
+      values.sync()
+    }
+  }
+}
@@ -1,17 +1,19 @@
 use core::sync::atomic::{Ordering, AtomicU64};
 use crate::cases::scan::{fold_sequential, scan_sequential, BLOCK_SIZE};
 use crate::cases::scan::row_column_chained::{ BlockInfo, Data, reset, STATE_PREFIX_AVAILABLE, STATE_AGGREGATE_AVAILABLE };
+use crate::cases::scan::lookback_statistics::LookbackStatistics;
 use crate::core::worker::*;
 use crate::core::task::*;
+use crate::core::trace;
 use crate::core::workassisting_loop::*;
-use crate::utils::array::MultArray;
+use crate::utils::array::Multidimensional;
 
-pub fn init_single<const N: usize>(input: &MultArray<AtomicU64, N>, temp: &[BlockInfo], output: &MultArray<AtomicU64, N>) -> Task {
+pub fn init_single<A: Multidimensional<AtomicU64>>(input: &A, temp: &[BlockInfo], output: &A) -> Task {
   reset(temp);
   create_task(input, temp, output)
 }
 
-fn create_task<const N: usize>(input_m: &MultArray<AtomicU64, N>, temp: &[BlockInfo], output_m: &MultArray<AtomicU64, N>) -> Task {
+fn create_task<A: Multidimensional<AtomicU64>>(input_m: &A, temp: &[BlockInfo], output_m: &A) -> Task {
   let inner_size = input_m.get_inner_size() as u64;
   let inner_rows = input_m.total_inner_count() as u64;
   let input = input_m.get_data();
@@ -20,11 +22,12 @@ fn create_task<const N: usize>(input_m: &MultArray<AtomicU64, N>, temp: &[BlockI
   let blocks_per_row = (inner_size + BLOCK_SIZE - 1) / BLOCK_SIZE;
   let block_count = blocks_per_row.checked_mul(inner_rows).expect("Block count overflowed u64 size") as u32;
 
-  Task::new_dataparallel::<Data>(run, finish, Data{ input, temp, output, blocks_per_row, inner_size:inner_size as u64 }, block_count, false)
+  Task::new_dataparallel::<Data>(run, finish, Data{ input, temp, output, blocks_per_row, inner_size:inner_size as u64, carry: 0 }, block_count, false)
 }
 
 fn run(_workers: &Workers, task: *const TaskObject<Data>, loop_arguments: LoopArguments) {
   let data = unsafe { TaskObject::get_data(task) };
+  let mut statistics = LookbackStatistics::default();
   let inner_rows = data.input.len() / data.inner_size as usize;
   let inner_size = data.inner_size as usize;
 
@@ -53,6 +56,7 @@ fn run(_workers: &Workers, task: *const TaskObject<Data>, loop_arguments: LoopAr
       };
 
     if let Some(aggregate) = aggregate_start {
+      statistics.record_immediate();
       let local = scan_sequential(&data.input[start .. end], aggregate, &data.output[start .. end]);
       data.temp[temp_idx as usize].prefix.store(local, Ordering::Relaxed);
       data.temp[temp_idx as usize].state.store(STATE_PREFIX_AVAILABLE, Ordering::Release);
@@ -66,18 +70,27 @@ fn run(_workers: &Workers, task: *const TaskObject<Data>, loop_arguments: LoopAr
       let mut aggregate = 0;
       let mut previous = temp_idx as usize - 1;
 
+      // Number of inspected predecessors and iterations spent waiting, for the tracer and the statistics
+      let lookback_span = trace::start();
+      let mut length = 0;
+      let mut spins = 0;
       loop {
         let previous_state = data.temp[previous as usize].state.load(Ordering::Acquire);
         if previous_state == STATE_PREFIX_AVAILABLE {
           aggregate = data.temp[previous as usize].prefix.load(Ordering::Acquire) + aggregate;
+          length += 1;
           break;
         } else if previous_state == STATE_AGGREGATE_AVAILABLE {
           aggregate = data.temp[previous as usize].aggregate.load(Ordering::Acquire) + aggregate;
+          length += 1;
           previous = previous - 1;
         } else {
           // Continue looping until the state of the previous block changes.
+          spins += 1;
         }
       }
+      trace::complete(lookback_span, "look-back", [("length", length), ("spins", spins)]);
+      statistics.record_lookback(length, spins);
 
       // Make aggregate available
       data.temp[temp_idx as usize].prefix.store(aggregate + local, Ordering::Relaxed);
@@ -86,6 +99,7 @@ fn run(_workers: &Workers, task: *const TaskObject<Data>, loop_arguments: LoopAr
       scan_sequential(&data.input[start .. end], aggregate, &data.output[start .. end]);
     }
   });
+  statistics.flush();
 }
 
 fn finish(workers: &Workers, task: *mut TaskObject<Data>) {
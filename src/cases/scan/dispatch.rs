@@ -0,0 +1,192 @@
+use core::sync::atomic::AtomicU64;
+use std::fs::File;
+use std::io::{prelude::*, BufReader, BufWriter};
+use crate::cases::scan::{fill, columnwise_chained, row_column_chained, sequential_rowbased, BLOCK_SIZE};
+use crate::cases::scan::row_column_chained::BlockInfo;
+use crate::core::task::*;
+use crate::core::worker::*;
+use crate::utils::array::{MultArray, Multidimensional};
+use crate::utils::benchmark::time;
+
+// Automatic selection of the multidimensional scan kernel.
+// The choice is based on the length of the rows (innermost dimension) and the number of rows per thread.
+// The decision table consists of measured points; a shape is dispatched to the strategy of the nearest point.
+
+// Default location of a calibrated decision table
+pub const TABLE_PATH: &str = "./dispatch-table.dat";
+
+// Number of elements of the arrays used during calibration
+const CALIBRATION_SIZE: usize = 1024 * 1024 * 16;
+// Row lengths used during calibration
+const CALIBRATION_INNER_SIZES: [usize; 8] = [64, 512, 4096, 16384, 65536, 262144, 1048576, 4194304];
+const CALIBRATION_RUNS: usize = 10;
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Strategy {
+  SequentialRowbased,
+  ColumnwiseChained,
+  RowColumnChained
+}
+
+impl Strategy {
+  pub const ALL: [Strategy; 3] = [Strategy::SequentialRowbased, Strategy::ColumnwiseChained, Strategy::RowColumnChained];
+
+  pub fn name(self) -> &'static str {
+    match self {
+      Strategy::SequentialRowbased => "sequential-rowbased",
+      Strategy::ColumnwiseChained => "columnwise-chained",
+      Strategy::RowColumnChained => "row-column-chained"
+    }
+  }
+
+  pub fn from_name(name: &str) -> Option<Strategy> {
+    Strategy::ALL.into_iter().find(|strategy| strategy.name() == name)
+  }
+
+  pub fn init_single<A: Multidimensional<AtomicU64>>(self, input: &A, temp: &[BlockInfo], output: &A) -> Task {
+    match self {
+      Strategy::SequentialRowbased => sequential_rowbased::create_task(input, output),
+      Strategy::ColumnwiseChained => columnwise_chained::init_single(input, temp, output),
+      Strategy::RowColumnChained => row_column_chained::init_single(input, temp, output)
+    }
+  }
+}
+
+// A single point of the decision table: the fastest strategy for a given shape and thread count.
+#[derive(Debug, Copy, Clone)]
+pub struct Decision {
+  pub inner_size: usize,
+  pub rows_per_thread: f64,
+  pub strategy: Strategy
+}
+
+#[derive(Debug, Clone)]
+pub struct DecisionTable {
+  decisions: Vec<Decision>
+}
+
+impl DecisionTable {
+  // Table based on the results on the Intel 12900:
+  // sequential row-based wins for short rows, column-wise chained for many medium rows
+  // and the assisting column-wise chained scan (row_column_chained) for long rows.
+  pub fn default_table() -> DecisionTable {
+    let block_size = BLOCK_SIZE as usize;
+    DecisionTable{ decisions: vec![
+      Decision{ inner_size: block_size / 4, rows_per_thread: 10000.0, strategy: Strategy::SequentialRowbased },
+      Decision{ inner_size: block_size, rows_per_thread: 1000.0, strategy: Strategy::SequentialRowbased },
+      Decision{ inner_size: block_size * 4, rows_per_thread: 500.0, strategy: Strategy::ColumnwiseChained },
+      Decision{ inner_size: block_size * 16, rows_per_thread: 16.0, strategy: Strategy::ColumnwiseChained },
+      Decision{ inner_size: block_size * 256, rows_per_thread: 1.0, strategy: Strategy::RowColumnChained },
+      Decision{ inner_size: block_size * 4096, rows_per_thread: 0.25, strategy: Strategy::RowColumnChained },
+    ]}
+  }
+
+  // Loads a calibrated table from 'path', or falls back to the default table if that file does not exist.
+  pub fn load_or_default(path: &str) -> DecisionTable {
+    match File::open(path) {
+      Ok(file) => DecisionTable::read(file).unwrap_or_else(|message| {
+        println!("Ignoring decision table {}: {}", path, message);
+        DecisionTable::default_table()
+      }),
+      Err(_) => DecisionTable::default_table()
+    }
+  }
+
+  fn read(file: File) -> Result<DecisionTable, String> {
+    let mut decisions = vec![];
+    for line in BufReader::new(file).lines() {
+      let line = line.map_err(|e| e.to_string())?;
+      if line.starts_with('#') || line.trim().is_empty() {
+        continue;
+      }
+      let columns: Vec<&str> = line.split('\t').collect();
+      if columns.len() != 3 {
+        return Err(format!("expected 3 columns in line '{}'", line));
+      }
+      let inner_size = columns[0].parse().map_err(|_| format!("invalid row length '{}'", columns[0]))?;
+      let rows_per_thread = columns[1].parse().map_err(|_| format!("invalid number of rows per thread '{}'", columns[1]))?;
+      let strategy = Strategy::from_name(columns[2]).ok_or(format!("unknown strategy '{}'", columns[2]))?;
+      decisions.push(Decision{ inner_size, rows_per_thread, strategy });
+    }
+    if decisions.is_empty() {
+      return Err("table is empty".to_owned());
+    }
+    Ok(DecisionTable{ decisions })
+  }
+
+  pub fn save(&self, path: &str) {
+    let file = File::create(path).unwrap();
+    let mut writer = BufWriter::new(&file);
+    writeln!(&mut writer, "# Decision table for the automatic scan dispatcher").unwrap();
+    writeln!(&mut writer, "# Row length\tRows per thread\tStrategy").unwrap();
+    for decision in &self.decisions {
+      writeln!(&mut writer, "{}\t{}\t{}", decision.inner_size, decision.rows_per_thread, decision.strategy.name()).unwrap();
+    }
+  }
+
+  pub fn select(&self, inner_size: usize, row_count: usize, thread_count: usize) -> Strategy {
+    if thread_count <= 1 {
+      // Scanning row by row is the sequential algorithm, hence it has no overhead on a single thread.
+      return Strategy::SequentialRowbased;
+    }
+
+    // Find the nearest decision, measured on a logarithmic scale in both dimensions.
+    let log_inner = (inner_size.max(1) as f64).log2();
+    let log_rows = (row_count.max(1) as f64 / thread_count as f64).log2();
+    let mut best = self.decisions[0].strategy;
+    let mut best_distance = f64::INFINITY;
+    for decision in &self.decisions {
+      let distance = (log_inner - (decision.inner_size.max(1) as f64).log2()).abs()
+        + (log_rows - decision.rows_per_thread.log2()).abs();
+      if distance < best_distance {
+        best = decision.strategy;
+        best_distance = distance;
+      }
+    }
+    best
+  }
+}
+
+// Single entry point: creates the task of the kernel that the decision table selects for this shape and thread count.
+pub fn init_single<A: Multidimensional<AtomicU64>>(table: &DecisionTable, thread_count: usize, input: &A, temp: &[BlockInfo], output: &A) -> Task {
+  let strategy = table.select(input.get_inner_size(), input.total_inner_count(), thread_count);
+  strategy.init_single(input, temp, output)
+}
+
+// Measures all strategies on this machine, and stores the fastest per shape and thread count in 'path'.
+pub fn calibrate(path: &str, thread_counts: &[usize]) -> DecisionTable {
+  println!();
+  println!("Calibrating the scan dispatcher");
+  let mut decisions = vec![];
+  for inner_size in CALIBRATION_INNER_SIZES {
+    let shape = [CALIBRATION_SIZE / inner_size, inner_size];
+    let input = unsafe { MultArray::new(shape) };
+    let output = unsafe { MultArray::new(shape) };
+    let temp = row_column_chained::create_temp(&input);
+    fill(input.get_data());
+
+    for &thread_count in thread_counts {
+      if thread_count == 1 {
+        // Not needed, as a single thread always uses the sequential row-based scan.
+        continue;
+      }
+
+      let mut fastest = (Strategy::RowColumnChained, u64::MAX);
+      for strategy in Strategy::ALL {
+        let (_, time) = time(CALIBRATION_RUNS, || {}, || {
+          let task = strategy.init_single(&input, &temp, &output);
+          Workers::run(thread_count, task);
+        });
+        if time < fastest.1 {
+          fastest = (strategy, time);
+        }
+      }
+      println!("  sh = {:?}, {:02} threads: {} ({} us)", shape, thread_count, fastest.0.name(), fastest.1);
+      decisions.push(Decision{ inner_size, rows_per_thread: shape[0] as f64 / thread_count as f64, strategy: fastest.0 });
+    }
+  }
+
+  let table = DecisionTable{ decisions };
+  table.save(path);
+  table
+}
@@ -0,0 +1,115 @@
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+// Optional counters of the look-back behaviour of the chained scans.
+// Every worker counts in a local LookbackStatistics and adds it to the shared counters once,
+// at the end of its part of a task, such that workers don't contend on the counters per block.
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LookbackStatistics {
+  // Blocks that were scanned directly, as the prefix of the previous block was available
+  // or the block is at the start of a row
+  pub immediate: u64,
+  // Blocks that had to perform a look-back
+  pub lookbacks: u64,
+  // Total number of predecessors visited in the look-backs
+  pub visited: u64,
+  // Largest number of predecessors visited in a single look-back
+  pub max_length: u64,
+  // Iterations of look-backs waiting on a predecessor without an aggregate
+  pub spins: u64,
+}
+
+impl LookbackStatistics {
+  #[inline(always)]
+  pub fn record_immediate(&mut self) {
+    self.immediate += 1;
+  }
+
+  #[inline(always)]
+  pub fn record_lookback(&mut self, length: u64, spins: u64) {
+    self.lookbacks += 1;
+    self.visited += length;
+    self.max_length = self.max_length.max(length);
+    self.spins += spins;
+  }
+
+  // Adds the counts of this worker to the shared counters
+  pub fn flush(&self) {
+    if !is_enabled() { return; }
+    IMMEDIATE.fetch_add(self.immediate, Ordering::Relaxed);
+    LOOKBACKS.fetch_add(self.lookbacks, Ordering::Relaxed);
+    VISITED.fetch_add(self.visited, Ordering::Relaxed);
+    MAX_LENGTH.fetch_max(self.max_length, Ordering::Relaxed);
+    SPINS.fetch_add(self.spins, Ordering::Relaxed);
+  }
+
+  pub fn blocks(&self) -> u64 {
+    self.immediate + self.lookbacks
+  }
+
+  // Averages the counts over a number of runs. The maximum look-back length is not averaged.
+  pub fn per_run(&self, runs: u64) -> LookbackAverages {
+    let runs = runs.max(1) as f64;
+    LookbackAverages{
+      immediate: self.immediate as f64 / runs,
+      lookbacks: self.lookbacks as f64 / runs,
+      visited: self.visited as f64 / runs,
+      max_length: self.max_length,
+      spins: self.spins as f64 / runs,
+    }
+  }
+}
+
+// Average counts of a run, see LookbackStatistics
+#[derive(Debug, Clone, Copy)]
+pub struct LookbackAverages {
+  pub immediate: f64,
+  pub lookbacks: f64,
+  pub visited: f64,
+  // Largest number of predecessors visited in a single look-back, over all runs
+  pub max_length: u64,
+  pub spins: f64,
+}
+
+impl LookbackAverages {
+  pub fn blocks(&self) -> f64 {
+    self.immediate + self.lookbacks
+  }
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static IMMEDIATE: AtomicU64 = AtomicU64::new(0);
+static LOOKBACKS: AtomicU64 = AtomicU64::new(0);
+static VISITED: AtomicU64 = AtomicU64::new(0);
+static MAX_LENGTH: AtomicU64 = AtomicU64::new(0);
+static SPINS: AtomicU64 = AtomicU64::new(0);
+
+pub fn set_enabled(enabled: bool) {
+  ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+  ENABLED.load(Ordering::Relaxed)
+}
+
+pub fn reset() {
+  for counter in [&IMMEDIATE, &LOOKBACKS, &VISITED, &MAX_LENGTH, &SPINS] {
+    counter.store(0, Ordering::Relaxed);
+  }
+}
+
+// Returns the counters since the last reset, or None if the statistics are disabled
+// or no chained scan was executed.
+pub fn take() -> Option<LookbackStatistics> {
+  if !is_enabled() {
+    return None;
+  }
+  let statistics = LookbackStatistics{
+    immediate: IMMEDIATE.swap(0, Ordering::Relaxed),
+    lookbacks: LOOKBACKS.swap(0, Ordering::Relaxed),
+    visited: VISITED.swap(0, Ordering::Relaxed),
+    max_length: MAX_LENGTH.swap(0, Ordering::Relaxed),
+    spins: SPINS.swap(0, Ordering::Relaxed),
+  };
+  if statistics.blocks() == 0 { None } else { Some(statistics) }
+}
@@ -0,0 +1,86 @@
+use core::sync::atomic::{Ordering, AtomicU32, AtomicU64, AtomicUsize};
+use crate::cases::scan::BLOCK_SIZE;
+use crate::core::worker::*;
+use crate::core::task::*;
+use crate::core::workassisting_loop::*;
+use crate::utils::numa::NodeClaims;
+
+// Data-parallel initialisation of arrays. Every element is generated from its index only,
+// such that the result is the same as a sequential fill, independent of the number of threads.
+// MultArray::new gives uninitialised memory, hence the fill is the first touch of the pages of an input.
+// By filling in parallel with the (pinned) workers, the pages are spread over the NUMA nodes
+// of the workers that will scan them, instead of all ending up on the node of the main thread.
+// Workers prefer the blocks of the part of the array of their own node (see utils::numa).
+
+// Number of elements per block of the fill task, spanning multiple pages
+const FILL_BLOCK_SIZE: usize = BLOCK_SIZE as usize * 4;
+
+// Number of threads used to fill arrays. Set to the largest thread count of the benchmarks.
+static THREAD_COUNT: AtomicUsize = AtomicUsize::new(1);
+
+pub fn set_thread_count(thread_count: usize) {
+  THREAD_COUNT.store(thread_count.max(1), Ordering::Relaxed);
+}
+
+pub fn thread_count() -> usize {
+  THREAD_COUNT.load(Ordering::Relaxed)
+}
+
+// Elements of the arrays that can be filled. The generated values are truncated to the width of the element.
+pub trait Element: Send + Sync + 'static {
+  fn store_value(&self, value: u64);
+}
+
+impl Element for AtomicU64 {
+  #[inline(always)]
+  fn store_value(&self, value: u64) { self.store(value, Ordering::Relaxed) }
+}
+
+impl Element for AtomicU32 {
+  #[inline(always)]
+  fn store_value(&self, value: u64) { self.store(value as u32, Ordering::Relaxed) }
+}
+
+struct Data<'a, T, F> {
+  values: &'a [T],
+  generate: F,
+  claims: NodeClaims
+}
+
+pub fn init<T: Element, F: Fn(usize) -> u64 + Send + Sync>(values: &[T], generate: F) -> Task {
+  let block_count = values.len().div_ceil(FILL_BLOCK_SIZE);
+  assert!(block_count <= u32::MAX as usize, "Block count overflowed u32 size");
+  Task::new_dataparallel::<Data<T, F>>(run::<T, F>, finish::<T, F>, Data{ values, generate, claims: NodeClaims::new(block_count as u32) }, block_count as u32, false)
+}
+
+// Stores generate(index) at every index of 'values', with thread_count() workers.
+// Small arrays are filled sequentially, as starting the workers would take longer.
+pub fn fill_with<T: Element, F: Fn(usize) -> u64 + Send + Sync>(values: &[T], generate: F) {
+  let thread_count = thread_count();
+  if thread_count == 1 || values.len() <= FILL_BLOCK_SIZE {
+    fill_sequential(values, 0, &generate);
+  } else {
+    Workers::run(thread_count, init(values, generate));
+  }
+}
+
+fn fill_sequential<T: Element, F: Fn(usize) -> u64>(values: &[T], offset: usize, generate: &F) {
+  for (idx, value) in values.iter().enumerate() {
+    value.store_value(generate(offset + idx));
+  }
+}
+
+fn run<T: Element, F: Fn(usize) -> u64 + Send + Sync>(_workers: &Workers, task: *const TaskObject<Data<T, F>>, loop_arguments: LoopArguments) {
+  let data = unsafe { TaskObject::get_data(task) };
+
+  workassisting_loop_numa!(loop_arguments, &data.claims, |block_index| {
+    let start = block_index as usize * FILL_BLOCK_SIZE;
+    let end = (start + FILL_BLOCK_SIZE).min(data.values.len());
+    fill_sequential(&data.values[start .. end], start, &data.generate);
+  });
+}
+
+fn finish<T: Element, F: Fn(usize) -> u64 + Send + Sync>(workers: &Workers, task: *mut TaskObject<Data<T, F>>) {
+  let _ = unsafe { TaskObject::take_data(task) };
+  workers.finish();
+}
@@ -0,0 +1,98 @@
+use core::sync::atomic::{Ordering, AtomicU64};
+use crate::cases::scan::{fold_sequential, scan_sequential, BLOCK_SIZE};
+use crate::cases::scan::row_column_chained::{ BlockInfo, Data, reset };
+use crate::core::worker::*;
+use crate::core::task::*;
+use crate::core::workassisting_loop::*;
+use crate::utils::array::Multidimensional;
+use crate::utils::numa::NodeClaims;
+
+// Classic three-phase reduce-then-scan:
+// 1. Every block is reduced to its aggregate (data parallel).
+// 2. The aggregates are scanned per row, giving the prefix of every block (sequential).
+// 3. Every block is scanned, starting from its prefix (data parallel).
+// Phase 2 and 3 are started from the finish function of the previous phase.
+// The blocks of phase 1 and 3 are independent, hence workers prefer the blocks on their own NUMA node.
+
+// Data of the phases, with the claims of the NUMA-aware loops
+pub struct PhaseData<'a> {
+  pub scan: Data<'a>,
+  pub claims: NodeClaims
+}
+
+pub fn init_single<A: Multidimensional<AtomicU64>>(input: &A, temp: &[BlockInfo], output: &A) -> Task {
+  reset(temp);
+  create_task(input, temp, output)
+}
+
+fn create_task<A: Multidimensional<AtomicU64>>(input_m: &A, temp: &[BlockInfo], output_m: &A) -> Task {
+  let inner_size = input_m.get_inner_size() as u64;
+  let inner_rows = input_m.total_inner_count() as u64;
+  let input = input_m.get_data();
+  let output = output_m.get_data();
+
+  let blocks_per_row = inner_size.div_ceil(BLOCK_SIZE);
+  let block_count = blocks_per_row.checked_mul(inner_rows).expect("Block count overflowed u64 size") as u32;
+
+  let data = PhaseData{ scan: Data{ input, temp, output, blocks_per_row, inner_size, carry: 0 }, claims: NodeClaims::new(block_count) };
+  Task::new_dataparallel::<PhaseData>(run_reduce, finish_reduce, data, block_count, false)
+}
+
+// Returns the start and end index of a block, where blocks never cross the boundary of a row.
+pub fn block_bounds(data: &Data<'_>, block_index: u32) -> (usize, usize) {
+  let row_idx = block_index as usize / data.blocks_per_row as usize;
+  let column_idx = block_index as usize - (row_idx * data.blocks_per_row as usize);
+
+  let row_start = row_idx * data.inner_size as usize;
+  let start = row_start + column_idx * BLOCK_SIZE as usize;
+  let end = (start + BLOCK_SIZE as usize).min(row_start + data.inner_size as usize);
+  (start, end)
+}
+
+// Computes the exclusive prefix of every block from the aggregates of the blocks.
+// The accumulator is reset at the first block of every row.
+pub fn scan_block_aggregates(temp: &[BlockInfo], blocks_per_row: u64) {
+  let mut accumulator = 0;
+  for (idx, info) in temp.iter().enumerate() {
+    if (idx as u64).is_multiple_of(blocks_per_row) {
+      accumulator = 0;
+    }
+    info.prefix.store(accumulator, Ordering::Relaxed);
+    accumulator += info.aggregate.load(Ordering::Relaxed);
+  }
+}
+
+fn run_reduce(_workers: &Workers, task: *const TaskObject<PhaseData>, loop_arguments: LoopArguments) {
+  let data = unsafe { TaskObject::get_data(task) };
+
+  workassisting_loop_numa!(loop_arguments, &data.claims, |block_index| {
+    let (start, end) = block_bounds(&data.scan, block_index);
+    let local = fold_sequential(&data.scan.input[start .. end]);
+    data.scan.temp[block_index as usize].aggregate.store(local, Ordering::Relaxed);
+  });
+}
+
+fn finish_reduce(workers: &Workers, task: *mut TaskObject<PhaseData>) {
+  let data = unsafe { TaskObject::take_data(task) };
+  // All threads have finished the reduce phase, so all aggregates are available.
+  scan_block_aggregates(data.scan.temp, data.scan.blocks_per_row);
+  data.claims.reset();
+
+  let block_count = data.scan.temp.len() as u32;
+  workers.push_task(Task::new_dataparallel::<PhaseData>(run_scan, finish, data, block_count, false));
+}
+
+fn run_scan(_workers: &Workers, task: *const TaskObject<PhaseData>, loop_arguments: LoopArguments) {
+  let data = unsafe { TaskObject::get_data(task) };
+
+  workassisting_loop_numa!(loop_arguments, &data.claims, |block_index| {
+    let (start, end) = block_bounds(&data.scan, block_index);
+    let prefix = data.scan.temp[block_index as usize].prefix.load(Ordering::Relaxed);
+    scan_sequential(&data.scan.input[start .. end], prefix, &data.scan.output[start .. end]);
+  });
+}
+
+fn finish(workers: &Workers, task: *mut TaskObject<PhaseData>) {
+  let _ = unsafe { TaskObject::take_data(task) };
+  workers.finish();
+}
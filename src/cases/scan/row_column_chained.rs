@@ -1,9 +1,11 @@
 use core::sync::atomic::{Ordering, AtomicU64, AtomicU32};
 use crate::cases::scan::{fold_sequential, scan_sequential, BLOCK_SIZE};
+use crate::cases::scan::lookback_statistics::LookbackStatistics;
 use crate::core::worker::*;
 use crate::core::task::*;
+use crate::core::trace;
 use crate::core::workassisting_loop::*;
-use crate::utils::array::MultArray;
+use crate::utils::array::{alloc_undef_u64_array, Buffer, Multidimensional};
 
 pub struct Data<'a> {
   pub input: &'a [AtomicU64],
@@ -11,6 +13,8 @@ pub struct Data<'a> {
   pub output: &'a [AtomicU64],
   pub blocks_per_row: u64,
   pub inner_size: u64,
+  // Initial value of every row, to continue the scan of a previous chunk (see chunked.rs)
+  pub carry: u64
 }
 
 pub struct BlockInfo {
@@ -23,10 +27,12 @@ pub const STATE_INITIALIZED: u64 = 0;
 pub const STATE_AGGREGATE_AVAILABLE: u64 = 1;
 pub const STATE_PREFIX_AVAILABLE: u64 = 2;
 
-pub fn create_temp<const N: usize>(input: &MultArray<AtomicU64, N>) -> Box<[BlockInfo]> {
-  (0 .. ((input.get_inner_size() as u64 + BLOCK_SIZE - 1) / BLOCK_SIZE) * input.total_inner_count() as u64).map(|_| BlockInfo{
-    state: AtomicU64::new(STATE_INITIALIZED), aggregate: AtomicU64::new(0), prefix: AtomicU64::new(0)
-  }).collect()
+// Allocated like the arrays, such that the NUMA placement applies to the block descriptors too
+pub fn create_temp<A: Multidimensional<AtomicU64>>(input: &A) -> Buffer<BlockInfo> {
+  let count = ((input.get_inner_size() as u64 + BLOCK_SIZE - 1) / BLOCK_SIZE) * input.total_inner_count() as u64;
+  let temp = unsafe { alloc_undef_u64_array(count as usize) };
+  reset(&temp);
+  temp
 }
 
 pub fn reset(temp: &[BlockInfo]) {
@@ -37,12 +43,18 @@ pub fn reset(temp: &[BlockInfo]) {
   }
 }
 
-pub fn init_single<const N: usize>(input: &MultArray<AtomicU64, N>, temp: &[BlockInfo], output: &MultArray<AtomicU64, N>) -> Task {
+pub fn init_single<A: Multidimensional<AtomicU64>>(input: &A, temp: &[BlockInfo], output: &A) -> Task {
   reset(temp);
-  create_task(input, temp, output)
+  create_task(input, temp, output, 0)
 }
 
-fn create_task<const N: usize>(input_m: &MultArray<AtomicU64, N>, temp: &[BlockInfo], output_m: &MultArray<AtomicU64, N>) -> Task {
+// Scans every row starting from 'carry' instead of zero
+pub fn init_carried<A: Multidimensional<AtomicU64>>(input: &A, temp: &[BlockInfo], output: &A, carry: u64) -> Task {
+  reset(temp);
+  create_task(input, temp, output, carry)
+}
+
+fn create_task<A: Multidimensional<AtomicU64>>(input_m: &A, temp: &[BlockInfo], output_m: &A, carry: u64) -> Task {
   let inner_size = input_m.get_inner_size() as u64;
   let inner_rows = input_m.total_inner_count() as u64;
   let input = input_m.get_data();
@@ -56,13 +68,14 @@ fn create_task<const N: usize>(input_m: &MultArray<AtomicU64, N>, temp: &[BlockI
         inner_rows.div_ceil(BLOCK_SIZE / inner_size) as u32
       };
   
-  Task::new_dataparallel::<Data>(run, finish, Data{ input, temp, output, blocks_per_row, inner_size:inner_size as u64 }, block_count, true)
+  Task::new_dataparallel::<Data>(run, finish, Data{ input, temp, output, blocks_per_row, inner_size:inner_size as u64, carry }, block_count, true)
 }
 
 fn run(_workers: &Workers, task: *const TaskObject<Data>, loop_arguments: LoopArguments) {
   let data = unsafe { TaskObject::get_data(task) };
   let inner_rows = data.input.len() / data.inner_size as usize;
   let segments = data.blocks_per_row as u32;
+  let mut statistics = LookbackStatistics::default();
 
   workassisting_loop_row_column!(loop_arguments, segments, 
   // Multiple-rows scan algorithm
@@ -70,10 +83,12 @@ fn run(_workers: &Workers, task: *const TaskObject<Data>, loop_arguments: LoopAr
     let rows_per_block = (BLOCK_SIZE as usize / data.inner_size as usize).min(inner_rows) as u64;
     let block_size = data.inner_size * rows_per_block;
     let mut start = block_index as usize * block_size as usize;
+    // The last block may contain fewer rows
+    let end = (start + block_size as usize).min(data.input.len());
 
-    for _ in 0 .. rows_per_block {
+    while start < end {
       let row_end = start + data.inner_size as usize;
-      scan_sequential(&data.input[start .. row_end], 0, &data.output[start .. row_end]);
+      scan_sequential(&data.input[start .. row_end], data.carry, &data.output[start .. row_end]);
       start = row_end;
     }
   },
@@ -82,7 +97,7 @@ fn run(_workers: &Workers, task: *const TaskObject<Data>, loop_arguments: LoopAr
     let row_idx = block_index as usize / data.blocks_per_row as usize;
     let column_idx = block_index as usize - (row_idx * data.blocks_per_row as usize);
     let descriptor_idx = block_index as usize;
-    adaptive_chained_lookback(data, row_idx, column_idx, descriptor_idx);
+    adaptive_chained_lookback(data, row_idx, column_idx, descriptor_idx, &mut statistics);
   },
   // Column-wise scan algorithm
   |block_index, rows_completed| {
@@ -90,11 +105,12 @@ fn run(_workers: &Workers, task: *const TaskObject<Data>, loop_arguments: LoopAr
     let row_idx = (block_index as usize % new_inner_rows as usize) + rows_completed as usize;
     let column_idx = block_index as usize / new_inner_rows as usize;
     let descriptor_idx = row_idx * data.blocks_per_row as usize + column_idx;
-    adaptive_chained_lookback(data, row_idx, column_idx, descriptor_idx);
+    adaptive_chained_lookback(data, row_idx, column_idx, descriptor_idx, &mut statistics);
   });
+  statistics.flush();
 }
 
-fn adaptive_chained_lookback(data:&Data<'_>, row_idx:usize, column_idx:usize, descriptor_idx:usize) {
+fn adaptive_chained_lookback(data:&Data<'_>, row_idx:usize, column_idx:usize, descriptor_idx:usize, statistics: &mut LookbackStatistics) {
   // Check if we already have a prefix of the previous block or
   // if the current block is at the start of a row.
   // If that is the case, then we can perform the scan directly.
@@ -103,7 +119,7 @@ fn adaptive_chained_lookback(data:&Data<'_>, row_idx:usize, column_idx:usize, de
   let end = row_idx * data.inner_size as usize + ((column_idx + 1) * BLOCK_SIZE as usize).min(data.inner_size as usize);
   
   let aggregate_start = if column_idx == 0 {
-    Some(0)
+    Some(data.carry)
   } else {
     let previous = descriptor_idx - 1;
     let previous_state = data.temp[previous].state.load(Ordering::Acquire);
@@ -115,6 +131,7 @@ fn adaptive_chained_lookback(data:&Data<'_>, row_idx:usize, column_idx:usize, de
   };
 
   if let Some(aggregate) = aggregate_start {
+    statistics.record_immediate();
     let local = scan_sequential(&data.input[start .. end], aggregate, &data.output[start .. end]);
     data.temp[descriptor_idx].prefix.store(local, Ordering::Relaxed);
     data.temp[descriptor_idx].state.store(STATE_PREFIX_AVAILABLE, Ordering::Release);
@@ -127,18 +144,27 @@ fn adaptive_chained_lookback(data:&Data<'_>, row_idx:usize, column_idx:usize, de
     let mut aggregate = 0;
     let mut previous = descriptor_idx - 1;
 
+    // Number of inspected predecessors and iterations spent waiting, for the tracer and the statistics
+    let lookback_span = trace::start();
+    let mut length = 0;
+    let mut spins = 0;
     loop {
       let previous_state = data.temp[previous].state.load(Ordering::Acquire);
       if previous_state == STATE_PREFIX_AVAILABLE {
         aggregate = data.temp[previous].prefix.load(Ordering::Acquire) + aggregate;
+        length += 1;
         break;
       } else if previous_state == STATE_AGGREGATE_AVAILABLE {
         aggregate = data.temp[previous].aggregate.load(Ordering::Acquire) + aggregate;
+        length += 1;
         previous = previous - 1;
       } else {
         // Continue looping until the state of the previous block changes.
+        spins += 1;
       }
     }
+    trace::complete(lookback_span, "look-back", [("length", length), ("spins", spins)]);
+    statistics.record_lookback(length, spins);
 
     // Share calculated prefix value
     data.temp[descriptor_idx].prefix.store(aggregate + local, Ordering::Relaxed);
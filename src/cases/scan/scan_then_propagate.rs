@@ -0,0 +1,74 @@
+use core::sync::atomic::{Ordering, AtomicU64};
+use crate::cases::scan::{scan_sequential, BLOCK_SIZE};
+use crate::cases::scan::reduce_then_scan::{block_bounds, scan_block_aggregates, PhaseData};
+use crate::cases::scan::row_column_chained::{ BlockInfo, Data, reset };
+use crate::core::worker::*;
+use crate::core::task::*;
+use crate::core::workassisting_loop::*;
+use crate::utils::array::Multidimensional;
+use crate::utils::numa::NodeClaims;
+
+// Classic three-phase scan-then-propagate:
+// 1. Every block is scanned locally, starting from zero (data parallel).
+// 2. The aggregates are scanned per row, giving the prefix of every block (sequential).
+// 3. The prefix of every block is added to all values of that block (data parallel).
+// Phase 2 and 3 are started from the finish function of the previous phase.
+// As in reduce-then-scan, workers prefer the blocks on their own NUMA node in phase 1 and 3.
+
+pub fn init_single<A: Multidimensional<AtomicU64>>(input: &A, temp: &[BlockInfo], output: &A) -> Task {
+  reset(temp);
+  create_task(input, temp, output)
+}
+
+fn create_task<A: Multidimensional<AtomicU64>>(input_m: &A, temp: &[BlockInfo], output_m: &A) -> Task {
+  let inner_size = input_m.get_inner_size() as u64;
+  let inner_rows = input_m.total_inner_count() as u64;
+  let input = input_m.get_data();
+  let output = output_m.get_data();
+
+  let blocks_per_row = inner_size.div_ceil(BLOCK_SIZE);
+  let block_count = blocks_per_row.checked_mul(inner_rows).expect("Block count overflowed u64 size") as u32;
+
+  let data = PhaseData{ scan: Data{ input, temp, output, blocks_per_row, inner_size, carry: 0 }, claims: NodeClaims::new(block_count) };
+  Task::new_dataparallel::<PhaseData>(run_scan, finish_scan, data, block_count, false)
+}
+
+fn run_scan(_workers: &Workers, task: *const TaskObject<PhaseData>, loop_arguments: LoopArguments) {
+  let data = unsafe { TaskObject::get_data(task) };
+
+  workassisting_loop_numa!(loop_arguments, &data.claims, |block_index| {
+    let (start, end) = block_bounds(&data.scan, block_index);
+    let local = scan_sequential(&data.scan.input[start .. end], 0, &data.scan.output[start .. end]);
+    data.scan.temp[block_index as usize].aggregate.store(local, Ordering::Relaxed);
+  });
+}
+
+fn finish_scan(workers: &Workers, task: *mut TaskObject<PhaseData>) {
+  let data = unsafe { TaskObject::take_data(task) };
+  // All threads have finished the local scans, so all aggregates are available.
+  scan_block_aggregates(data.scan.temp, data.scan.blocks_per_row);
+  data.claims.reset();
+
+  let block_count = data.scan.temp.len() as u32;
+  workers.push_task(Task::new_dataparallel::<PhaseData>(run_propagate, finish, data, block_count, false));
+}
+
+fn run_propagate(_workers: &Workers, task: *const TaskObject<PhaseData>, loop_arguments: LoopArguments) {
+  let data = unsafe { TaskObject::get_data(task) };
+
+  workassisting_loop_numa!(loop_arguments, &data.claims, |block_index| {
+    let prefix = data.scan.temp[block_index as usize].prefix.load(Ordering::Relaxed);
+    // The first block of every row already contains its final values.
+    if prefix != 0 {
+      let (start, end) = block_bounds(&data.scan, block_index);
+      for value in &data.scan.output[start .. end] {
+        value.store(value.load(Ordering::Relaxed) + prefix, Ordering::Relaxed);
+      }
+    }
+  });
+}
+
+fn finish(workers: &Workers, task: *mut TaskObject<PhaseData>) {
+  let _ = unsafe { TaskObject::take_data(task) };
+  workers.finish();
+}
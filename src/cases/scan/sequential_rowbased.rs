@@ -3,7 +3,7 @@ use crate::cases::scan::scan_sequential;
 use crate::core::worker::*;
 use crate::core::task::*;
 use crate::core::workassisting_loop::*;
-use crate::utils::array::MultArray;
+use crate::utils::array::Multidimensional;
 
 struct Data<'a> {
   input: &'a [AtomicU64],
@@ -11,7 +11,7 @@ struct Data<'a> {
   inner_size: u64
 }
 
-pub fn create_task<const N: usize>(input_m: &MultArray<AtomicU64, N>, output_m: &MultArray<AtomicU64, N>) -> Task {
+pub fn create_task<A: Multidimensional<AtomicU64>>(input_m: &A, output_m: &A) -> Task {
   let inner_size = input_m.get_inner_size();
   let inner_rows = input_m.total_inner_count();
   let input = input_m.get_data();
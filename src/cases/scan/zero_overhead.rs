@@ -1,8 +1,10 @@
 use core::sync::atomic::{Ordering, AtomicU64};
 use crate::cases::scan::{fold_sequential, scan_sequential, BLOCK_SIZE};
 use crate::cases::scan::row_column_chained::{ BlockInfo, reset, STATE_PREFIX_AVAILABLE, STATE_AGGREGATE_AVAILABLE };
+use crate::cases::scan::lookback_statistics::LookbackStatistics;
 use crate::core::worker::*;
 use crate::core::task::*;
+use crate::core::trace;
 use crate::core::workassisting_loop::*;
 
 pub fn init_single(input: &[AtomicU64], temp: &[BlockInfo], output: &[AtomicU64]) -> Task {
@@ -22,6 +24,7 @@ fn create_task(input: &[AtomicU64], temp: &[BlockInfo], output: &[AtomicU64]) ->
 
 fn run(_workers: &Workers, task: *const TaskObject<Data>, loop_arguments: LoopArguments) {
   let data = unsafe { TaskObject::get_data(task) };
+  let mut statistics = LookbackStatistics::default();
   let mut sequential = true;
   workassisting_loop!(loop_arguments, |block_index| {
     let start = block_index as usize * BLOCK_SIZE as usize;
@@ -45,6 +48,7 @@ fn run(_workers: &Workers, task: *const TaskObject<Data>, loop_arguments: LoopAr
     };
 
     if let Some(aggregate) = aggregate_start {
+      statistics.record_immediate();
       let local = scan_sequential(&data.input[start .. end], aggregate, &data.output[start .. end]);
       data.temp[block_index as usize].prefix.store(local, Ordering::Relaxed);
       data.temp[block_index as usize].state.store(STATE_PREFIX_AVAILABLE, Ordering::Release);
@@ -59,18 +63,27 @@ fn run(_workers: &Workers, task: *const TaskObject<Data>, loop_arguments: LoopAr
       let mut aggregate = 0;
       let mut previous = block_index - 1;
 
+      // Number of inspected predecessors and iterations spent waiting, for the tracer and the statistics
+      let lookback_span = trace::start();
+      let mut length = 0;
+      let mut spins = 0;
       loop {
         let previous_state = data.temp[previous as usize].state.load(Ordering::Acquire);
         if previous_state == STATE_PREFIX_AVAILABLE {
           aggregate = data.temp[previous as usize].prefix.load(Ordering::Acquire) + aggregate;
+          length += 1;
           break;
         } else if previous_state == STATE_AGGREGATE_AVAILABLE {
           aggregate = data.temp[previous as usize].aggregate.load(Ordering::Acquire) + aggregate;
+          length += 1;
           previous = previous - 1;
         } else {
           // Continue looping until the state of previous block changes.
+          spins += 1;
         }
       }
+      trace::complete(lookback_span, "look-back", [("length", length), ("spins", spins)]);
+      statistics.record_lookback(length, spins);
 
       // Make aggregate available
       data.temp[block_index as usize].prefix.store(aggregate + local, Ordering::Relaxed);
@@ -79,6 +92,7 @@ fn run(_workers: &Workers, task: *const TaskObject<Data>, loop_arguments: LoopAr
       scan_sequential(&data.input[start .. end], aggregate, &data.output[start .. end]);
     }
   });
+  statistics.flush();
 }
 
 fn finish(workers: &Workers, task: *mut TaskObject<Data>) {
@@ -0,0 +1,116 @@
+use core::sync::atomic::{Ordering, AtomicU64};
+use std::sync::Mutex;
+use crate::cases::scan::parallel_fill;
+use crate::core::worker::*;
+use crate::core::task::*;
+use crate::core::workassisting_loop::*;
+use crate::utils::array::{alloc_undef_u64_array, Buffer};
+use crate::utils::benchmark::time_samples;
+
+// STREAM-style measurement of the memory bandwidth, as a reference for the bandwidth-bound scans.
+// Copy (c = a) has the same access pattern as an out-of-place scan: one read and one write per element.
+// Triad (a = b + q * c) reads two arrays and writes one.
+// Like the scans, the kernels use AtomicU64 with relaxed loads and stores.
+
+// Number of elements per array: 128 MB per array, much larger than the caches
+pub const STREAM_SIZE: usize = 1024 * 1024 * 16;
+const STREAM_BLOCK_SIZE: usize = 1024 * 16;
+const STREAM_RUNS: usize = 10;
+const TRIAD_SCALAR: u64 = 3;
+
+// Bandwidth in GB/s
+#[derive(Debug, Clone, Copy)]
+pub struct Bandwidth {
+  pub copy: f64,
+  pub triad: f64,
+}
+
+// Measured bandwidth per thread count, such that it is only measured once per run
+static MEASURED: Mutex<Vec<(usize, Bandwidth)>> = Mutex::new(Vec::new());
+
+// Returns the bandwidth for the given thread count, measuring it on first use.
+pub fn peak_bandwidth(thread_count: usize) -> Bandwidth {
+  if let Some(&(_, bandwidth)) = MEASURED.lock().unwrap().iter().find(|(count, _)| *count == thread_count) {
+    return bandwidth;
+  }
+  let bandwidth = measure(thread_count);
+  println!("  STREAM with {:02} threads: copy {:.1} GB/s, triad {:.1} GB/s", thread_count, bandwidth.copy, bandwidth.triad);
+  MEASURED.lock().unwrap().push((thread_count, bandwidth));
+  bandwidth
+}
+
+// Returns all bandwidths measured in this run, ordered by thread count
+pub fn measured() -> Vec<(usize, Bandwidth)> {
+  let mut measured = MEASURED.lock().unwrap().clone();
+  measured.sort_by_key(|&(thread_count, _)| thread_count);
+  measured
+}
+
+fn measure(thread_count: usize) -> Bandwidth {
+  let a: Buffer<AtomicU64> = unsafe { alloc_undef_u64_array(STREAM_SIZE) };
+  let b: Buffer<AtomicU64> = unsafe { alloc_undef_u64_array(STREAM_SIZE) };
+  let c: Buffer<AtomicU64> = unsafe { alloc_undef_u64_array(STREAM_SIZE) };
+  // Filled by the workers, such that the pages are placed like the arrays of the scans
+  parallel_fill::fill_with(&a, |_| 1);
+  parallel_fill::fill_with(&b, |_| 2);
+  parallel_fill::fill_with(&c, |_| 0);
+
+  // Like STREAM, use the fastest run
+  let (_, copy) = time_samples(STREAM_RUNS, || {}, || {
+    Workers::run(thread_count, create_task(Kernel::Copy, &a, &b, &c));
+  });
+  let (_, triad) = time_samples(STREAM_RUNS, || {}, || {
+    Workers::run(thread_count, create_task(Kernel::Triad, &a, &b, &c));
+  });
+
+  let bytes = (STREAM_SIZE * std::mem::size_of::<u64>()) as f64;
+  Bandwidth{
+    copy: 2.0 * bytes / *copy.iter().min().unwrap() as f64,
+    triad: 3.0 * bytes / *triad.iter().min().unwrap() as f64,
+  }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kernel {
+  Copy,
+  Triad
+}
+
+struct Data<'a> {
+  kernel: Kernel,
+  a: &'a [AtomicU64],
+  b: &'a [AtomicU64],
+  c: &'a [AtomicU64]
+}
+
+fn create_task<'a>(kernel: Kernel, a: &'a [AtomicU64], b: &'a [AtomicU64], c: &'a [AtomicU64]) -> Task {
+  Task::new_dataparallel::<Data>(run, finish, Data{ kernel, a, b, c }, a.len().div_ceil(STREAM_BLOCK_SIZE) as u32, false)
+}
+
+fn run(_workers: &Workers, task: *const TaskObject<Data>, loop_arguments: LoopArguments) {
+  let data = unsafe { TaskObject::get_data(task) };
+
+  workassisting_loop!(loop_arguments, |block_index| {
+    let start = block_index as usize * STREAM_BLOCK_SIZE;
+    let end = (start + STREAM_BLOCK_SIZE).min(data.a.len());
+
+    match data.kernel {
+      Kernel::Copy => {
+        for i in start .. end {
+          data.c[i].store(data.a[i].load(Ordering::Relaxed), Ordering::Relaxed);
+        }
+      },
+      Kernel::Triad => {
+        for i in start .. end {
+          let value = data.b[i].load(Ordering::Relaxed).wrapping_add(TRIAD_SCALAR.wrapping_mul(data.c[i].load(Ordering::Relaxed)));
+          data.a[i].store(value, Ordering::Relaxed);
+        }
+      }
+    }
+  });
+}
+
+fn finish(workers: &Workers, task: *mut TaskObject<Data>) {
+  let _ = unsafe { TaskObject::take_data(task) };
+  workers.finish();
+}
@@ -1,3 +1,4 @@
 pub mod task;
+pub mod trace;
 pub mod workassisting_loop;
 pub mod worker;
@@ -24,6 +24,9 @@ pub struct TaskObject<T> {
   // Hence we can run the finish function and deallocate the task.
   pub(super) active_threads: AtomicI32,
   pub(super) work_index: AtomicU32,
+  // Number of rows that are scanned row-wise in a two-sided task, agreed on by the first column-wise claim.
+  // ROWS_COMPLETED_UNKNOWN until then (see workassisting_loop_row_column).
+  pub(super) work_rows_completed: AtomicU32,
   pub(super) work_size: u32,
   pub(super) work_two_sided: bool,
   pub data: T,
@@ -58,6 +61,7 @@ impl Task {
       work_size,
       active_threads: AtomicI32::new(0),
       work_index: AtomicU32::new(if work_two_sided { 1 << 16 } else { 1 }),
+      work_rows_completed: AtomicU32::new(ROWS_COMPLETED_UNKNOWN),
       work_two_sided,
       data
     });
@@ -74,6 +78,7 @@ impl Task {
       work_size: 0,
       active_threads: AtomicI32::new(0),
       work_index: AtomicU32::new(0),
+      work_rows_completed: AtomicU32::new(ROWS_COMPLETED_UNKNOWN),
       work_two_sided: false,
       data
     });
@@ -125,9 +130,12 @@ impl<T> TaskObject<T> {
   }
 }
 
+pub const ROWS_COMPLETED_UNKNOWN: u32 = u32::MAX;
+
 pub struct LoopArguments<'a> {
   pub work_size: u32,
   pub work_index: &'a AtomicU32,
+  pub rows_completed: &'a AtomicU32,
   pub empty_signal: EmptySignal<'a>,
   pub first_index: u32,
 }
@@ -0,0 +1,149 @@
+use core::cell::RefCell;
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::fs::File;
+use std::io::{prelude::*, BufWriter};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+// Optional tracer of the scheduler, recording per-thread events: tasks and assists, claimed blocks,
+// switches between row-wise and column-wise scanning, task_empty signals and look-backs.
+// Events are stored in a thread-local buffer and collected when the worker thread finishes.
+// When tracing is disabled, recording an event only costs a relaxed load.
+// The events can be exported in the Chrome trace-event format, which can be opened in
+// chrome://tracing or https://ui.perfetto.dev.
+
+#[derive(Debug, Clone, Copy)]
+pub struct Event {
+  pub name: &'static str,
+  // Start time in nanoseconds since begin
+  pub time: u64,
+  // Duration in nanoseconds, or None for an instant event
+  pub duration: Option<u64>,
+  // Up to two named arguments. Unused arguments have an empty name.
+  pub args: [(&'static str, u64); 2],
+}
+
+pub const NO_ARGS: [(&str, u64); 2] = [("", 0), ("", 0)];
+
+// Events of one thread, with the index of the thread
+pub type ThreadEvents = (usize, Vec<Event>);
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static BASE: OnceLock<Instant> = OnceLock::new();
+// Time of the last call to begin, relative to BASE
+static OFFSET: AtomicU64 = AtomicU64::new(0);
+static COLLECTED: Mutex<Vec<ThreadEvents>> = Mutex::new(Vec::new());
+
+thread_local! {
+  static BUFFER: RefCell<ThreadEvents> = const { RefCell::new((0, Vec::new())) };
+}
+
+#[inline(always)]
+pub fn is_enabled() -> bool {
+  ENABLED.load(Ordering::Relaxed)
+}
+
+fn since_base() -> u64 {
+  BASE.get_or_init(Instant::now).elapsed().as_nanos() as u64
+}
+
+fn now() -> u64 {
+  since_base() - OFFSET.load(Ordering::Relaxed)
+}
+
+// Starts recording events. Timestamps are relative to this call.
+pub fn begin() {
+  COLLECTED.lock().unwrap().clear();
+  OFFSET.store(since_base(), Ordering::Relaxed);
+  ENABLED.store(true, Ordering::Release);
+}
+
+// Stops recording and returns the events of all threads that called finish_thread.
+pub fn end() -> Vec<ThreadEvents> {
+  ENABLED.store(false, Ordering::Release);
+  let mut collected = std::mem::take(&mut *COLLECTED.lock().unwrap());
+  collected.sort_by_key(|(thread_index, _)| *thread_index);
+  collected
+}
+
+// Marks the current thread as the worker with the given index
+pub fn start_thread(thread_index: usize) {
+  if !is_enabled() { return; }
+  BUFFER.with(|buffer| {
+    let mut buffer = buffer.borrow_mut();
+    buffer.0 = thread_index;
+    buffer.1.clear();
+  });
+}
+
+// Moves the events of the current thread to the collected events
+pub fn finish_thread() {
+  if !is_enabled() { return; }
+  let events = BUFFER.with(|buffer| {
+    let mut buffer = buffer.borrow_mut();
+    (buffer.0, std::mem::take(&mut buffer.1))
+  });
+  COLLECTED.lock().unwrap().push(events);
+}
+
+// Returns the start time of a span, or None if tracing is disabled
+#[inline(always)]
+pub fn start() -> Option<u64> {
+  if is_enabled() { Some(now()) } else { None }
+}
+
+// Records a span that started at 'start'
+#[inline(always)]
+pub fn complete(start: Option<u64>, name: &'static str, args: [(&'static str, u64); 2]) {
+  if let Some(time) = start {
+    let duration = now() - time;
+    push(Event{ name, time, duration: Some(duration), args });
+  }
+}
+
+#[inline(always)]
+pub fn instant(name: &'static str, args: [(&'static str, u64); 2]) {
+  if is_enabled() {
+    push(Event{ name, time: now(), duration: None, args });
+  }
+}
+
+fn push(event: Event) {
+  BUFFER.with(|buffer| buffer.borrow_mut().1.push(event));
+}
+
+// Writes traced runs in the Chrome trace-event format. Every run is shown as a separate process,
+// with the label of the run as process name and a row per worker thread.
+pub fn write_chrome_trace(path: &str, runs: &[(String, Vec<ThreadEvents>)]) {
+  let file = File::create(path).unwrap();
+  let mut writer = BufWriter::new(&file);
+  writeln!(&mut writer, "{{\"displayTimeUnit\": \"ns\", \"traceEvents\": [").unwrap();
+  let mut first = true;
+  let mut separator = |writer: &mut BufWriter<&File>| {
+    if !first {
+      writeln!(writer, ",").unwrap();
+    }
+    first = false;
+  };
+
+  for (pid, (label, threads)) in runs.iter().enumerate() {
+    separator(&mut writer);
+    write!(&mut writer, "{{\"name\": \"process_name\", \"ph\": \"M\", \"pid\": {}, \"args\": {{\"name\": \"{}\"}}}}", pid, label.replace('"', "'")).unwrap();
+    for (thread_index, events) in threads {
+      separator(&mut writer);
+      write!(&mut writer, "{{\"name\": \"thread_name\", \"ph\": \"M\", \"pid\": {}, \"tid\": {}, \"args\": {{\"name\": \"Worker {}\"}}}}", pid, thread_index, thread_index).unwrap();
+      for event in events {
+        separator(&mut writer);
+        let args: Vec<String> = event.args.iter().filter(|(name, _)| !name.is_empty()).map(|(name, value)| format!("\"{}\": {}", name, value)).collect();
+        // Timestamps in the trace format are in microseconds
+        match event.duration {
+          Some(duration) => write!(&mut writer, "{{\"name\": \"{}\", \"ph\": \"X\", \"pid\": {}, \"tid\": {}, \"ts\": {:.3}, \"dur\": {:.3}, \"args\": {{{}}}}}",
+            event.name, pid, thread_index, event.time as f64 / 1000.0, duration as f64 / 1000.0, args.join(", ")).unwrap(),
+          None => write!(&mut writer, "{{\"name\": \"{}\", \"ph\": \"i\", \"s\": \"t\", \"pid\": {}, \"tid\": {}, \"ts\": {:.3}, \"args\": {{{}}}}}",
+            event.name, pid, thread_index, event.time as f64 / 1000.0, args.join(", ")).unwrap()
+        }
+      }
+    }
+  }
+  writeln!(&mut writer, "\n]}}").unwrap();
+}
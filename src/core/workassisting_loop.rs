@@ -1,3 +1,6 @@
+use core::sync::atomic::{AtomicU32, Ordering};
+use crate::core::task::ROWS_COMPLETED_UNKNOWN;
+
 #[macro_export]
 macro_rules! workassisting_loop {
   ($loop_arguments_expr: expr, |$block_index: ident| $body: block) => {
@@ -13,7 +16,9 @@ macro_rules! workassisting_loop {
 
       // Copy block_idx to an immutable variable, such that a user of this macro cannot mutate it.
       let $block_index: u32 = block_idx;
+      let trace_span = $crate::core::trace::start();
       $body
+      $crate::core::trace::complete(trace_span, "block", [("block", block_idx as u64), ("", 0)]);
 
       block_idx = loop_arguments.work_index.fetch_add(1, Ordering::Relaxed);
     }
@@ -22,6 +27,39 @@ macro_rules! workassisting_loop {
 }
 pub(crate) use workassisting_loop;
 
+// Variant of the workassisting loop for tasks of which the blocks are independent, which prefers blocks
+// that are resident on the NUMA node of the worker. The shared work index hands out tickets, such that
+// the task is still signalled empty when all work is claimed. Every ticket is exchanged for a block
+// with the NodeClaims of the task.
+#[macro_export]
+macro_rules! workassisting_loop_numa {
+  ($loop_arguments_expr: expr, $claims_expr: expr, |$block_index: ident| $body: block) => {
+    let mut loop_arguments: LoopArguments = $loop_arguments_expr;
+    let claims: &$crate::utils::numa::NodeClaims = $claims_expr;
+    let node = $crate::utils::numa::current_node();
+    // Claim work
+    let mut ticket = loop_arguments.first_index;
+
+    while ticket < loop_arguments.work_size {
+      if ticket == loop_arguments.work_size - 1 {
+        // All work is claimed.
+        loop_arguments.empty_signal.task_empty();
+      }
+
+      let block_idx = claims.claim(node);
+      // Copy block_idx to an immutable variable, such that a user of this macro cannot mutate it.
+      let $block_index: u32 = block_idx;
+      let trace_span = $crate::core::trace::start();
+      $body
+      $crate::core::trace::complete(trace_span, "block", [("block", block_idx as u64), ("node", node as u64)]);
+
+      ticket = loop_arguments.work_index.fetch_add(1, Ordering::Relaxed);
+    }
+    loop_arguments.empty_signal.task_empty();
+  };
+}
+pub(crate) use workassisting_loop_numa;
+
 // The two-sided workassisting loop for row-column scanning
 // The first thread starts sequential with row-wise scanning
 // Parallel threads start columnwise scanning on the next (unclaimed) row
@@ -52,7 +90,9 @@ macro_rules! workassisting_loop_row_column {
 
       loop {  
         let $block_index_1 = block_idx;
+        let trace_span = $crate::core::trace::start();
         $multiple_rows_scan
+        $crate::core::trace::complete(trace_span, "multi-row block", [("block", block_idx as u64), ("", 0)]);
 
         block_idx = work_index.fetch_add(1, Ordering::Relaxed) & 0xFFFF;
 
@@ -76,17 +116,23 @@ macro_rules! workassisting_loop_row_column {
 
       if rowwise_thread { // Execute the first row-wise block (index 0)
         let $block_index_2 = rowwise_idx;
+        let trace_span = $crate::core::trace::start();
         $row_wise_scan
+        $crate::core::trace::complete(trace_span, "row-wise block", [("block", rowwise_idx as u64), ("", 0)]);
       } else { // Determine if column-wise scanning is possible
-        rowwise_claimed_rows = (rowwise_idx + segments - 1) / segments;
+        rowwise_claimed_rows = $crate::core::workassisting_loop::agree_rows_completed(loop_arguments.rows_completed, (rowwise_idx + segments - 1) / segments);
         rowwise_work_size = rowwise_claimed_rows * segments;
         colwise_work_size = work_size - (rowwise_claimed_rows * segments);
 
-        if colwise_work_size > 0 {
+        // Other threads may already have claimed all column-wise blocks
+        if colwise_idx < colwise_work_size {
           let $block_index_3 = colwise_idx;
           let $rows_completed = rowwise_claimed_rows;
+          let trace_span = $crate::core::trace::start();
           $column_wise_scan
-        } else { // No (unclaimed) rows available, assist with row-wise scanning
+          $crate::core::trace::complete(trace_span, "column-wise block", [("block", colwise_idx as u64), ("rows_completed", rowwise_claimed_rows as u64)]);
+        } else { // No (unclaimed) column-wise blocks available, assist with row-wise scanning
+          $crate::core::trace::instant("assist row-wise", $crate::core::trace::NO_ARGS);
           rowwise_thread = true;
         }
       } 
@@ -98,7 +144,9 @@ macro_rules! workassisting_loop_row_column {
 
           if res.is_ok() {
             let $block_index_2 = rowwise_idx;
+            let trace_span = $crate::core::trace::start();
             $row_wise_scan
+            $crate::core::trace::complete(trace_span, "row-wise block", [("block", rowwise_idx as u64), ("", 0)]);
           } 
           
           block_idx = work_index.load(Ordering::Relaxed);
@@ -107,10 +155,13 @@ macro_rules! workassisting_loop_row_column {
 
           if colwise_idx > 0 {
             // Parallel thread(s) joined the computation, finish current row and then switch to column-wise
-            rowwise_claimed_rows = (rowwise_idx + segments - 1) / segments;
+            rowwise_claimed_rows = $crate::core::workassisting_loop::agree_rows_completed(loop_arguments.rows_completed, (rowwise_idx + segments - 1) / segments);
             rowwise_work_size = rowwise_claimed_rows * segments;
             colwise_work_size = work_size - rowwise_work_size;
             rowwise_thread = rowwise_idx < rowwise_work_size;
+            if !rowwise_thread {
+              $crate::core::trace::instant("switch to column-wise", [("rows_completed", rowwise_claimed_rows as u64), ("", 0)]);
+            }
           }
 
           let claimed = rowwise_idx + colwise_idx.min(colwise_work_size) + 1;
@@ -138,16 +189,29 @@ macro_rules! workassisting_loop_row_column {
           }
 
           if colwise_idx >= colwise_work_size { // Column-wise scan is finished, assist row-wise
+            $crate::core::trace::instant("assist row-wise", $crate::core::trace::NO_ARGS);
             rowwise_thread = true;
             continue;
           }
 
           let $block_index_3 = colwise_idx;
           let $rows_completed = rowwise_claimed_rows;
+          let trace_span = $crate::core::trace::start();
           $column_wise_scan
+          $crate::core::trace::complete(trace_span, "column-wise block", [("block", colwise_idx as u64), ("rows_completed", rowwise_claimed_rows as u64)]);
         } 
       }
     }
   }
 }
-pub(crate) use workassisting_loop_row_column;
\ No newline at end of file
+pub(crate) use workassisting_loop_row_column;
+
+// Publishes the number of rows that are scanned row-wise, or returns the number that another thread published first.
+// Threads derive this number from the work index at the moment they observe a column-wise claim. All threads must use
+// the same number, as it determines the layout of the column-wise blocks, hence only the first proposal is used.
+pub fn agree_rows_completed(rows_completed: &AtomicU32, proposal: u32) -> u32 {
+  match rows_completed.compare_exchange(ROWS_COMPLETED_UNKNOWN, proposal, Ordering::Relaxed, Ordering::Relaxed) {
+    Ok(_) => proposal,
+    Err(agreed) => agreed
+  }
+}
\ No newline at end of file
@@ -2,8 +2,10 @@ use core::sync::atomic::AtomicBool;
 use core::sync::atomic::Ordering;
 use crossbeam::deque;
 use crate::core::task::*;
+use crate::core::trace;
 use crate::utils::ptr::AtomicTaggedPtr;
 use crate::utils::ptr::TaggedPtr;
+use crate::utils::perf;
 use crate::utils::thread_pinning::AFFINITY_MAPPING;
 
 pub struct Workers<'a> {
@@ -39,7 +41,11 @@ impl<'a> Workers<'a> {
           activities: &activities
         };
         s.spawn(move || {
+          let counters = perf::ThreadCounters::start();
+          trace::start_thread(thread_index);
           workers.do_work(thread_index);
+          trace::finish_thread();
+          perf::stop(counters);
         });
       }
       affinity::set_thread_affinity(full).unwrap();
@@ -138,7 +144,9 @@ impl<'a> Workers<'a> {
         self.end_task(task);
         break;
       }
+      let span = trace::start();
       self.call_task(task, signal, current_index);
+      trace::complete(span, "assist", [("worker", other_index as u64), ("first_index", current_index as u64)]);
       break;
     }
   }
@@ -169,13 +177,17 @@ impl<'a> Workers<'a> {
     self.activities[thread_index].store(TaggedPtr::new(task_ptr, 0), Ordering::Release);
 
     let signal = EmptySignal{ pointer: &self.activities[thread_index], task: task_ref, state: EmptySignalState::Main };
+    // The task may be deallocated by call_task, hence read work_size before.
+    let work_size = task_ref.work_size;
+    let span = trace::start();
     self.call_task(unsafe { &*task_ptr }, signal, 0);
+    trace::complete(span, "task", [("work_size", work_size as u64), ("", 0)]);
   }
 
   // Calls the work function of a task, and calls end_task afterwards
   fn call_task(&self, task: *const TaskObject<()>, signal: EmptySignal, first_index: u32) {
     let task_ref = unsafe { &*task };
-    (task_ref.work.unwrap())(self, task, LoopArguments{ work_size: task_ref.work_size, work_index: &task_ref.work_index, empty_signal: signal, first_index});
+    (task_ref.work.unwrap())(self, task, LoopArguments{ work_size: task_ref.work_size, work_index: &task_ref.work_index, rows_completed: &task_ref.work_rows_completed, empty_signal: signal, first_index});
     self.end_task(task);
   }
 
@@ -211,6 +223,9 @@ enum EmptySignalState {
 
 impl<'a> EmptySignal<'a> {
   pub fn task_empty(&mut self) {
+    if !matches!(self.state, EmptySignalState::DidSignal) {
+      trace::instant("task_empty", trace::NO_ARGS);
+    }
     match self.state {
       EmptySignalState::DidSignal => {},
       EmptySignalState::Main => {
@@ -4,28 +4,193 @@ mod utils;
 
 use std::{path::Path, io::stdin};
 use crate::utils::thread_pinning;
+use crate::utils::cli::CppMode;
 
 fn main() {
-  let cpp_enabled = setup_cpp();
+  let algorithms: Vec<&str> = cases::scan::ALGORITHMS.iter()
+    .chain(cases::scan::CPP_ALGORITHMS.iter())
+    .chain(cases::compact::ALGORITHMS.iter())
+    .chain(cases::radix_sort::ALGORITHMS.iter())
+    .chain(cases::partition::ALGORITHMS.iter())
+    .chain(cases::recurrence::ALGORITHMS.iter())
+    .chain(cases::rle::ALGORITHMS.iter())
+    .chain(cases::reduce::ALGORITHMS.iter())
+    .copied().collect();
+  let mut options = match utils::cli::parse(std::env::args().skip(1), &algorithms) {
+    Ok(options) => options,
+    Err(message) => {
+      eprintln!("{}", message);
+      eprintln!("{}", utils::cli::USAGE);
+      std::process::exit(2);
+    }
+  };
+
+  if options.help {
+    println!("{}", utils::cli::USAGE);
+    return;
+  }
+
+  // Inputs are initialised by the workers, such that their pages are spread over the threads of the benchmarks
+  cases::scan::parallel_fill::set_thread_count(options.settings.max_threads());
+  // Placement of the pages of all arrays that are allocated from here on
+  utils::numa::set_placement(options.numa);
+  utils::huge_pages::set_mode(options.huge_pages);
+  let node_count = utils::numa::topology().node_count();
+  if node_count > 1 {
+    println!("Running on {} NUMA nodes with {:?} placement.", node_count, options.numa);
+  }
+
+  if options.calibrate {
+    cases::scan::dispatch::calibrate(cases::scan::dispatch::TABLE_PATH, &options.settings.thread_counts);
+    return;
+  }
+
+  if options.verify {
+    // Sizes of one-dimensional benchmarks are verified as shapes with a single dimension.
+    let mut shapes: Vec<Vec<usize>> = options.sizes.iter().flatten().map(|&size| vec![size]).collect();
+    shapes.extend(options.shapes.iter().flatten().cloned());
+    if shapes.is_empty() {
+      shapes = cases::scan::VERIFY_SHAPES.iter().map(|shape| shape.to_vec()).collect();
+    }
+    if !cases::verify(&options.settings, &shapes) {
+      eprintln!("Verification failed");
+      std::process::exit(1);
+    }
+    return;
+  }
+
+  if let Some(input_path) = &options.scan_file {
+    // The shape is given as a single size or shape
+    let mut shapes: Vec<Vec<usize>> = options.sizes.iter().flatten().map(|&size| vec![size]).collect();
+    shapes.extend(options.shapes.iter().flatten().cloned());
+    if shapes.len() != 1 {
+      eprintln!("--scan-file requires a single shape, given with --shapes or --sizes");
+      std::process::exit(2);
+    }
+    let start = std::time::Instant::now();
+    if let Err(message) = cases::scan::chunked::scan_file(options.settings.max_threads(), &shapes[0], input_path, options.output_file.as_deref()) {
+      eprintln!("{}", message);
+      std::process::exit(1);
+    }
+    println!("Scanned {} with shape {:?} in {:.3} s", input_path, shapes[0], start.elapsed().as_secs_f64());
+    return;
+  }
+
+  if options.compare_only {
+    if options.baseline.is_none() {
+      eprintln!("--compare-only requires --baseline");
+      std::process::exit(2);
+    }
+    compare_with_baseline(&options);
+    return;
+  }
 
-  if !cpp_enabled {
+  options.settings.cpp_enabled = setup_cpp(options.cpp);
+  utils::perf::set_enabled(options.perf_counters);
+  cases::scan::lookback_statistics::set_enabled(options.lookback_statistics);
+
+  if !options.settings.cpp_enabled {
     println!("Running the benchmarks without the C++ implementations.");
   }
 
   // affinity::set_thread_affinity([thread_pinning::AFFINITY_MAPPING[0]]).unwrap();
 
-  cases::scan::run_rowwise_vs_columnwise();
-  cases::scan::run_custom_shape(cases::scan::HIGHERDIM_SHAPE);
-  cases::scan::run_custom_shape(cases::scan::FOURDIM_SHAPE);
-  cases::scan::run(cpp_enabled);
-  cases::scan::run_multidim(cpp_enabled);
-  cases::scan::run_inplace(cpp_enabled);
-  cases::scan::run_inplace_multidim(cpp_enabled);
+  let settings = &options.settings;
+  let sizes = options.sizes.as_deref().unwrap_or(&[cases::scan::ONEDIM_SIZE]);
+  // Shapes passed on the command line replace the default shapes of all multidimensional benchmarks.
+  let shapes_or = |default: &[&[usize]]| -> Vec<Vec<usize>> {
+    match &options.shapes {
+      Some(shapes) => shapes.clone(),
+      None => default.iter().map(|shape| shape.to_vec()).collect()
+    }
+  };
+  let shapes = shapes_or(cases::scan::MULTIDIM_SHAPES);
+
+  if options.includes_benchmark("row-vs-column") {
+    cases::scan::run_rowwise_vs_columnwise(settings, &shapes_or(cases::scan::ROW_VS_COLUMN_SHAPES));
+  }
+  if options.includes_benchmark("higherdim") {
+    cases::scan::run_custom_shape(settings, &shapes_or(cases::scan::HIGHERDIM_SHAPE));
+  }
+  if options.includes_benchmark("fourdim") {
+    cases::scan::run_custom_shape(settings, &shapes_or(cases::scan::FOURDIM_SHAPE));
+  }
+  if options.includes_benchmark("scan") {
+    cases::scan::run(settings, sizes);
+  }
+  if options.includes_benchmark("scan-multidim") {
+    cases::scan::run_multidim(settings, &shapes);
+  }
+  if options.includes_benchmark("inplace") {
+    cases::scan::run_inplace(settings, sizes);
+  }
+  if options.includes_benchmark("inplace-multidim") {
+    cases::scan::run_inplace_multidim(settings, &shapes);
+  }
+  if options.includes_benchmark("compact") {
+    cases::compact::run_compact(settings, sizes);
+  }
+  if options.includes_benchmark("compact-multidim") {
+    cases::compact::run_compact_multidim(settings, &shapes);
+  }
+  if options.includes_benchmark("radix-sort") {
+    cases::radix_sort::run_radix_sort(settings, sizes);
+  }
+  if options.includes_benchmark("partition") {
+    cases::partition::run_partition(settings, sizes);
+  }
+  if options.includes_benchmark("partition-multidim") {
+    cases::partition::run_partition_multidim(settings, &shapes);
+  }
+  if options.includes_benchmark("recurrence") {
+    cases::recurrence::run_recurrence(settings, &shapes);
+  }
+  if options.includes_benchmark("rle") {
+    cases::rle::run_rle(settings, sizes);
+  }
+  if options.includes_benchmark("rle-multidim") {
+    cases::rle::run_rle_multidim(settings, &shapes);
+  }
+  if options.includes_benchmark("reduce") {
+    cases::reduce::run_reduce(settings, sizes);
+  }
+  if options.includes_benchmark("reduce-multidim") {
+    cases::reduce::run_reduce_multidim(settings, &shapes);
+  }
+  if options.includes_benchmark("histogram") {
+    cases::reduce::run_histogram(settings, sizes);
+  }
+  if options.includes_benchmark("histogram-multidim") {
+    cases::reduce::run_histogram_multidim(settings, &shapes);
+  }
+
+  compare_with_baseline(&options);
+}
+
+// Compares the results in the output directory against the baseline, if one was given.
+// Exits with status 1 if a regression was found.
+fn compare_with_baseline(options: &utils::cli::Options) {
+  let Some(baseline) = &options.baseline else { return; };
+  match utils::compare::compare(baseline, &options.settings.output_dir, options.threshold) {
+    Ok(0) => {},
+    Ok(regressions) => {
+      eprintln!("Found {} regressions compared to {}", regressions, baseline);
+      std::process::exit(1);
+    },
+    Err(message) => {
+      eprintln!("{}", message);
+      std::process::exit(2);
+    }
+  }
 }
 
 // Utility to install and build the c++ implementation.
-fn setup_cpp() -> bool {
-  if !Path::new("./reference-cpp/build").is_dir() {
+fn setup_cpp(mode: CppMode) -> bool {
+  if mode == CppMode::Disabled {
+    return false;
+  }
+
+  if mode == CppMode::Ask && !Path::new("./reference-cpp/build").is_dir() {
     println!("This benchmark program provides a reference implementation in C++. This requires Linux and clang++.");
     println!("Do you want to enable the C++ reference implementation? y/n");
     
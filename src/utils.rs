@@ -1,4 +1,13 @@
 pub mod array;
 pub mod benchmark;
+pub mod chart;
+pub mod cli;
+pub mod compare;
+pub mod huge_pages;
+pub mod mapped_file;
+pub mod metadata;
+pub mod numa;
+pub mod perf;
 pub mod ptr;
+pub mod statistics;
 pub mod thread_pinning;
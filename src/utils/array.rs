@@ -1,57 +1,220 @@
-pub unsafe fn alloc_undef_u64_array<T>(length: usize) -> Box<[T]> {
-  let mut vector = Vec::with_capacity(length);
-  vector.set_len(length);
-  vector.into_boxed_slice()
+use core::fmt::Debug;
+use core::ops::{Deref, DerefMut};
+use crate::utils::huge_pages::{self, Mapping};
+use crate::utils::mapped_file::{self, Access};
+
+// Storage of a large array, either on the heap, in a separate mapping with huge pages (see utils::huge_pages),
+// or in a mapped file (see utils::mapped_file)
+pub struct Buffer<T> {
+  pointer: *mut T,
+  length: usize,
+  // None if the array is allocated on the heap
+  mapping: Option<Mapping>
+}
+
+// The pages are placed on the NUMA nodes before they are touched (see utils::numa)
+pub unsafe fn alloc_undef_u64_array<T>(length: usize) -> Buffer<T> {
+  let bytes = length.checked_mul(std::mem::size_of::<T>()).expect("vector capacity overflowed usize");
+  let buffer = match huge_pages::map(bytes) {
+    Some(mapping) => Buffer{ pointer: mapping.address as *mut T, length, mapping: Some(mapping) },
+    None => {
+      let mut vector: Vec<T> = Vec::with_capacity(length);
+      vector.set_len(length);
+      Buffer{ pointer: Box::into_raw(vector.into_boxed_slice()) as *mut T, length, mapping: None }
+    }
+  };
+  crate::utils::numa::place(&buffer);
+  buffer
+}
+
+// Maps an array of 'length' elements from a file. Only suitable for element types that are valid for any bit pattern.
+pub fn map_file_array<T>(path: &str, length: usize, access: Access) -> Result<Buffer<T>, String> {
+  let bytes = length.checked_mul(std::mem::size_of::<T>()).expect("vector capacity overflowed usize");
+  match mapped_file::map(path, bytes, access)? {
+    Some(mapping) => Ok(Buffer{ pointer: mapping.address as *mut T, length, mapping: Some(mapping) }),
+    None => Ok(Buffer{ pointer: std::ptr::NonNull::dangling().as_ptr(), length: 0, mapping: None })
+  }
+}
+
+impl<T> Buffer<T> {
+  // Writes the array back to its file, if it is mapped from a file
+  pub fn sync(&self) -> Result<(), String> {
+    match &self.mapping {
+      Some(mapping) => mapped_file::sync(mapping),
+      None => Ok(())
+    }
+  }
+}
+
+impl<T> Deref for Buffer<T> {
+  type Target = [T];
+
+  fn deref(&self) -> &[T] {
+    unsafe { std::slice::from_raw_parts(self.pointer, self.length) }
+  }
+}
+
+impl<T> DerefMut for Buffer<T> {
+  fn deref_mut(&mut self) -> &mut [T] {
+    unsafe { std::slice::from_raw_parts_mut(self.pointer, self.length) }
+  }
+}
+
+impl<T> Drop for Buffer<T> {
+  fn drop(&mut self) {
+    match &self.mapping {
+      Some(mapping) => huge_pages::unmap(mapping),
+      None => drop(unsafe { Box::from_raw(std::ptr::slice_from_raw_parts_mut(self.pointer, self.length)) })
+    }
+  }
+}
+
+impl<T: Debug> Debug for Buffer<T> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    self.deref().fmt(f)
+  }
+}
+
+// Like a Box<[T]>, a Buffer owns its elements
+unsafe impl<T: Send> Send for Buffer<T> {}
+unsafe impl<T: Sync> Sync for Buffer<T> {}
+
+// Common interface of MultArray (rank known at compile time) and DynMultArray (rank known at runtime).
+// The scan kernels only need the data and the layout of the innermost rows.
+pub trait Multidimensional<T> {
+  fn get_data(&self) -> &[T];
+
+  // Retrieve the size of the innermost dimension
+  fn get_inner_size(&self) -> usize;
+
+  // Calculate the total number of rows on the innermost dimension
+  fn total_inner_count(&self) -> usize;
 }
 
 #[derive(Debug)]
 pub struct MultArray<T, const N: usize> {
-  data: Box<[T]>,
+  data: Buffer<T>,
   shape: [usize; N],
 }
 
 impl<T, const N: usize> MultArray<T, N> {
-  fn calc_size(shape: [usize; N]) -> usize {
-    let mut cap = 1;
-    for x in shape {
-        cap = usize::checked_mul(cap, x).expect("vector capacity overflowed usize");
-    }
-    cap
-  }
-
   pub unsafe fn new(shape: [usize; N]) -> Self {
-    let length = Self::calc_size(shape);
+    let length = calc_size(&shape);
     MultArray { data: alloc_undef_u64_array(length), shape }
   }
 
   // Retrieve the size of the innermost dimension
   pub fn get_inner_size(&self) -> usize {
-    match self.shape.last() {
-      None => 0,
-      Some(val) => *val
-    }
+    inner_size(&self.shape)
   }
 
-  pub fn store(&mut self, data: Box<[T]>) {
+  pub fn store(&mut self, data: Buffer<T>) {
     self.data = data;
   }
-  
+
   // Calculate the total number of rows on the innermost dimension
   pub fn total_inner_count(&self) -> usize {
-    let mut count = 1;
-    let size = self.shape.len();
-
-    if size == 0 {0}
-    else if size == 1 {1}
-    else {
-      for &x in self.shape.split_last().unwrap().1 {
-        count = usize::checked_mul(count, x).expect("vector capactiy overflowed usize");
-      }
-      count
-    }
+    inner_count(&self.shape)
+  }
+
+  pub fn get_data(&self) -> &Buffer<T> {
+    &self.data
+  }
+}
+
+impl<T, const N: usize> Multidimensional<T> for MultArray<T, N> {
+  fn get_data(&self) -> &[T] {
+    MultArray::get_data(self)
+  }
+
+  fn get_inner_size(&self) -> usize {
+    MultArray::get_inner_size(self)
+  }
+
+  fn total_inner_count(&self) -> usize {
+    MultArray::total_inner_count(self)
+  }
+}
+
+// Variant of MultArray of which the number of dimensions is only known at runtime,
+// for instance when the shape is passed on the command line.
+#[derive(Debug)]
+pub struct DynMultArray<T> {
+  data: Buffer<T>,
+  shape: Vec<usize>,
+}
+
+impl<T> DynMultArray<T> {
+  pub unsafe fn new(shape: &[usize]) -> Self {
+    let length = calc_size(shape);
+    DynMultArray { data: alloc_undef_u64_array(length), shape: shape.to_vec() }
   }
 
-  pub fn get_data(&self) -> &Box<[T]> {
+  // Array backed by the file at 'path', which contains the elements in row-major order
+  pub fn open(path: &str, shape: &[usize], access: Access) -> Result<Self, String> {
+    let length = calc_size(shape);
+    Ok(DynMultArray { data: map_file_array(path, length, access)?, shape: shape.to_vec() })
+  }
+
+  // Writes the array back to its file, if it is backed by a file
+  pub fn sync(&self) -> Result<(), String> {
+    self.data.sync()
+  }
+
+  // Retrieve the size of the innermost dimension
+  pub fn get_inner_size(&self) -> usize {
+    inner_size(&self.shape)
+  }
+
+  // Calculate the total number of rows on the innermost dimension
+  pub fn total_inner_count(&self) -> usize {
+    inner_count(&self.shape)
+  }
+
+  pub fn get_data(&self) -> &[T] {
     &self.data
-  } 
-}
\ No newline at end of file
+  }
+}
+
+impl<T> Multidimensional<T> for DynMultArray<T> {
+  fn get_data(&self) -> &[T] {
+    DynMultArray::get_data(self)
+  }
+
+  fn get_inner_size(&self) -> usize {
+    DynMultArray::get_inner_size(self)
+  }
+
+  fn total_inner_count(&self) -> usize {
+    DynMultArray::total_inner_count(self)
+  }
+}
+
+fn calc_size(shape: &[usize]) -> usize {
+  let mut cap = 1;
+  for &x in shape {
+      cap = usize::checked_mul(cap, x).expect("vector capacity overflowed usize");
+  }
+  cap
+}
+
+fn inner_size(shape: &[usize]) -> usize {
+  match shape.last() {
+    None => 0,
+    Some(val) => *val
+  }
+}
+
+fn inner_count(shape: &[usize]) -> usize {
+  let mut count = 1;
+  let size = shape.len();
+
+  if size == 0 {0}
+  else if size == 1 {1}
+  else {
+    for &x in shape.split_last().unwrap().1 {
+      count = usize::checked_mul(count, x).expect("vector capactiy overflowed usize");
+    }
+    count
+  }
+}
@@ -2,20 +2,184 @@ use core::fmt::Debug;
 use std::time;
 use std::fs::File;
 use std::io::{prelude::*, BufWriter};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use crate::utils::chart::{self, Chart, Line};
+use crate::cases::scan::lookback_statistics::{self, LookbackAverages};
+use crate::cases::stream::{self, Bandwidth};
+use crate::core::trace::{self, ThreadEvents};
+use crate::utils::huge_pages;
+use crate::utils::metadata;
+use crate::utils::perf::{self, Counters};
+use crate::utils::statistics::Statistics;
 
 pub struct Benchmarker<T> {
+  settings: Settings,
   chart_style: ChartStyle,
   name: String,
+  shape: Vec<usize>,
   max_speedup: u32,
   max_threads: u32,
   reference_time: u64,
+  reference_samples: Vec<u64>,
+  reference_statistics: Statistics,
+  reference_counters: Option<Counters>,
+  // Bytes read and written per element, to compute the achieved bandwidth
+  bytes_per_element: f64,
   reference_time_cpp: Option<u64>,
   expected: T,
-  output: Vec<(String, u32, Option<u32>, bool, Vec<f32>)>
+  output: Vec<Series>,
+  // Traced runs, with a label of the algorithm and thread count
+  traces: Vec<(String, Vec<ThreadEvents>)>
+}
+
+// The results of one parallel implementation, per thread count
+struct Series {
+  name: String,
+  chart_line_style: u32,
+  point_type: Option<u32>,
+  ours: bool,
+  speedups: Vec<f32>,
+  // Average time in microseconds
+  times: Vec<u64>,
+  // Time of each run in nanoseconds
+  samples: Vec<Vec<u64>>,
+  statistics: Vec<Statistics>,
+  // Average hardware counters of a run, if enabled
+  counters: Vec<Option<Counters>>,
+  // Achieved bandwidth in GB/s
+  bandwidths: Vec<f64>,
+  // Average look-back statistics of a run, if enabled and the algorithm is a chained scan
+  lookback: Vec<Option<LookbackAverages>>
 }
 
 pub const THREAD_COUNTS: [usize; 14] = [1, 2, 3, 4, 6, 8, 10, 12, 14, 16, 20, 24, 28, 32];
 pub const RUNS: usize = 50;
+// Upper bound on the number of runs when the number of runs is adaptive
+pub const MAX_RUNS: usize = 1000;
+
+// Configuration of a benchmark run, shared by all benchmarks.
+#[derive(Debug, Clone)]
+pub struct Settings {
+  pub thread_counts: Vec<usize>,
+  // The number of runs, or the minimal number of runs if precision is set
+  pub runs: usize,
+  // If set, runs are repeated until the 95% confidence interval of the mean is within
+  // this fraction of the mean (e.g. 0.01 for 1%), or until max_runs is reached.
+  pub precision: Option<f64>,
+  pub max_runs: usize,
+  pub output_dir: String,
+  // Names of the parallel implementations to run, or None to run all of them.
+  pub algorithms: Option<Vec<String>>,
+  pub cpp_enabled: bool,
+  // Whether the memory bandwidth of the machine is measured, to report the fraction of the peak bandwidth
+  pub bandwidth: bool,
+  // Whether an additional run per algorithm and thread count is traced
+  pub trace: bool,
+}
+
+impl Default for Settings {
+  fn default() -> Self {
+    Settings {
+      thread_counts: THREAD_COUNTS.into_iter().filter(|&thread_count| thread_count <= 16).collect(),
+      runs: RUNS,
+      precision: None,
+      max_runs: MAX_RUNS,
+      output_dir: default_output_dir(),
+      algorithms: None,
+      cpp_enabled: false,
+      bandwidth: true,
+      trace: false
+    }
+  }
+}
+
+impl Settings {
+  pub fn includes_algorithm(&self, name: &str) -> bool {
+    match &self.algorithms {
+      None => true,
+      Some(algorithms) => algorithms.iter().any(|algorithm| *algorithm == algorithm_id(name))
+    }
+  }
+
+  pub fn max_threads(&self) -> usize {
+    self.thread_counts.iter().copied().max().unwrap_or(1)
+  }
+}
+
+// Results directory named after the CPU, e.g. "./results-12th-gen-intel-core-i9-12900k",
+// such that results of different machines are not mixed.
+pub fn default_output_dir() -> String {
+  let cpu_model = &metadata::get().cpu_model;
+  if cpu_model == "unknown" {
+    "./results".to_owned()
+  } else {
+    "./results-".to_owned() + &sanitize(&cpu_model.to_lowercase()).replace('_', "-")
+  }
+}
+
+// Replaces all characters other than ASCII letters, digits, '-' and '.' by '_',
+// and removes repeated and leading or trailing underscores.
+fn sanitize(name: &str) -> String {
+  let mut result = String::new();
+  for c in name.chars() {
+    if c.is_ascii_alphanumeric() || c == '-' || c == '.' {
+      result.push(c);
+    } else if !result.is_empty() && !result.ends_with('_') {
+      result.push('_');
+    }
+  }
+  result.trim_end_matches('_').to_owned()
+}
+
+// Benchmarks written in this run, as (file name, benchmark name)
+static WRITTEN_BENCHMARKS: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+
+pub const INDEX_FILE: &str = "index.txt";
+
+// Returns the file name (without directory and extension) for the artifacts of a benchmark.
+// If an earlier benchmark in this run got the same name, a numeric suffix is added.
+fn artifact_name(name: &str) -> String {
+  let mut written = WRITTEN_BENCHMARKS.lock().unwrap();
+  let base = sanitize(name);
+  let mut file_name = base.clone();
+  let mut suffix = 2;
+  while written.iter().any(|(existing, _)| *existing == file_name) {
+    file_name = format!("{}-{}", base, suffix);
+    suffix += 1;
+  }
+  written.push((file_name.clone(), name.to_owned()));
+  file_name
+}
+
+// Writes the index file, listing every benchmark written in this run with the file name of its artifacts.
+fn write_index(output_dir: &str) {
+  let written = WRITTEN_BENCHMARKS.lock().unwrap();
+  let file = File::create(output_dir.to_owned() + "/" + INDEX_FILE).unwrap();
+  let mut writer = BufWriter::new(&file);
+  writeln!(&mut writer, "# Benchmarks of the run at {}, as file name and benchmark name", metadata::get().timestamp).unwrap();
+  for (file_name, name) in written.iter() {
+    writeln!(&mut writer, "{}\t{}", file_name, name).unwrap();
+  }
+}
+
+// Writes report.html, with the charts of all benchmarks written in this run
+fn write_report(output_dir: &str) {
+  let written = WRITTEN_BENCHMARKS.lock().unwrap();
+  let charts: Vec<(String, String)> = written.iter()
+    .filter_map(|(file_name, name)| std::fs::read_to_string(output_dir.to_owned() + "/" + file_name + ".svg").ok().map(|svg| (name.clone(), svg)))
+    .collect();
+  let metadata = metadata::get();
+  let title = format!("Benchmarks on {} ({} threads)", metadata.cpu_model, metadata.cpu_count);
+  std::fs::write(output_dir.to_owned() + "/report.html", chart::html_report(&title, &charts)).unwrap();
+}
+
+static GNUPLOT_WARNED: AtomicBool = AtomicBool::new(false);
+
+// Identifier of an algorithm as used on the command line, e.g. "Column-wise chained" becomes "column-wise-chained".
+pub fn algorithm_id(name: &str) -> String {
+  name.to_lowercase().replace(' ', "-")
+}
 
 #[derive(PartialEq, Eq, Copy, Clone)]
 pub enum ChartStyle {
@@ -23,39 +187,143 @@ pub enum ChartStyle {
   WithoutKey
 }
 
-pub fn benchmark<T: Debug + Eq, P: FnMut() -> (), Ref: FnMut() -> T>(chart_style: ChartStyle, name: &str, prepare: P, reference: Ref) -> Benchmarker<T> {
-  benchmark_with_max_speedup(chart_style, name, prepare, reference, 16, 3)
+// 'bytes_per_element' is the number of bytes that the benchmark reads and writes per element, for the achieved bandwidth
+pub fn benchmark<T: Debug + Eq, P: FnMut() -> (), Ref: FnMut() -> T>(settings: &Settings, chart_style: ChartStyle, name: &str, shape: &[usize], bytes_per_element: f64, prepare: P, reference: Ref) -> Benchmarker<T> {
+  let chart = ChartSettings{ style: chart_style, max_threads: settings.max_threads() as u32, max_speedup: 3 };
+  benchmark_with_max_speedup(settings, chart, name, shape, bytes_per_element, prepare, reference)
+}
+
+// Layout of the chart of a benchmark: whether it has a key, and the ranges of the axes
+#[derive(Copy, Clone)]
+pub struct ChartSettings {
+  pub style: ChartStyle,
+  pub max_threads: u32,
+  pub max_speedup: u32
 }
 
-pub fn benchmark_with_max_speedup<T: Debug + Eq, P: FnMut() -> (), Ref: FnMut() -> T>(chart_style: ChartStyle, name: &str, prepare: P, reference: Ref, max_threads: u32, max_speedup: u32) -> Benchmarker<T> {
+pub fn benchmark_with_max_speedup<T: Debug + Eq, P: FnMut() -> (), Ref: FnMut() -> T>(settings: &Settings, chart: ChartSettings, name: &str, shape: &[usize], bytes_per_element: f64, prepare: P, reference: Ref) -> Benchmarker<T> {
+  let ChartSettings{ style: chart_style, max_threads, max_speedup } = chart;
   println!("");
   println!("Benchmark {}", name);
-  let (expected, reference_time) = time(RUNS, prepare, reference);
-  println!("Sequential   {} ms", reference_time / 1000);
-  Benchmarker{ chart_style, name: name.to_owned(), max_threads, max_speedup, reference_time, reference_time_cpp: None, expected, output: vec![] }
+  let (expected, reference_samples) = measure(settings, prepare, reference);
+  let reference_time = mean_micros(&reference_samples);
+  let reference_statistics = Statistics::new(&reference_samples);
+  let reference_counters = perf::take_per_run();
+  let elements: usize = shape.iter().product();
+  let peak = if settings.bandwidth { Some(stream::peak_bandwidth(1)) } else { None };
+  println!("Sequential   {}, {}", format_statistics(&reference_statistics), format_bandwidth(achieved_bandwidth(elements, bytes_per_element, reference_statistics.mean), peak));
+  if let Some(counters) = &reference_counters {
+    println!("             {}", format_counters(counters, shape.iter().product(), reference_statistics.mean));
+  }
+  Benchmarker{ settings: settings.clone(), chart_style, name: name.to_owned(), shape: shape.to_vec(), max_threads, max_speedup, reference_time, reference_samples, reference_statistics, reference_counters, bytes_per_element, reference_time_cpp: None, expected, output: vec![], traces: vec![] }
 }
 
 impl<T: Copy + Debug + Eq + Send> Benchmarker<T> {
   pub fn parallel<Prepare: FnMut() -> (), Par: FnMut(usize) -> T>(mut self, name: &str, chart_line_style: u32, point_type: Option<u32>, our: bool, mut prepare: Prepare, mut parallel: Par) -> Self {
+    if !self.settings.includes_algorithm(name) {
+      return self;
+    }
+
     println!("{}", name);
-    let mut results = vec![];
-    for thread_count in THREAD_COUNTS {
+    let mut series = Series{ name: name.to_owned(), chart_line_style, point_type, ours: our, speedups: vec![], times: vec![], samples: vec![], statistics: vec![], counters: vec![], bandwidths: vec![], lookback: vec![] };
+    for &thread_count in &self.settings.thread_counts {
       if thread_count > self.max_threads as usize {
         break;
       }
 
-      let (value, time) = time(RUNS, || { prepare() }, || parallel(thread_count));
+      // Measure the peak bandwidth before the benchmark, as it resets the performance counters
+      let peak = if self.settings.bandwidth { Some(stream::peak_bandwidth(thread_count)) } else { None };
+      lookback_statistics::reset();
+      let (value, samples) = measure(&self.settings, || { prepare() }, || parallel(thread_count));
       assert_eq!(self.expected, value);
+      // The statistics include the warm-up run
+      let lookback = lookback_statistics::take().map(|statistics| statistics.per_run(samples.len() as u64 + 1));
+      let time = mean_micros(&samples);
+      let statistics = Statistics::new(&samples);
+      let relative = self.reference_time as f32 / time as f32;
+      let counters = perf::take_per_run();
+      let bandwidth = achieved_bandwidth(self.shape.iter().product(), self.bytes_per_element, statistics.mean);
+      println!("  {:02} threads {} ({:.2}x, {})", thread_count, format_statistics(&statistics), relative, format_bandwidth(bandwidth, peak));
+      series.bandwidths.push(bandwidth);
+      if let Some(counters) = &counters {
+        println!("             {}", format_counters(counters, self.shape.iter().product(), statistics.mean));
+      }
+      series.counters.push(counters);
+      if let Some(lookback) = &lookback {
+        println!("             {}", format_lookback(lookback));
+      }
+      series.lookback.push(lookback);
+
+      if self.settings.trace {
+        // Separate run, such that the tracer doesn't influence the timings
+        prepare();
+        trace::begin();
+        parallel(thread_count);
+        self.traces.push((format!("{} ({} threads)", name, thread_count), trace::end()));
+      }
+      series.speedups.push(relative);
+      series.times.push(time);
+      series.samples.push(samples);
+      series.statistics.push(statistics);
+    }
+    self.output.push(series);
+    self
+  }
+
+  // Runs a parallel C++ reference implementation (TBB, OpenMP or std::execution::par) for every thread count.
+  // The implementation is skipped if the reference was built without its library.
+  pub fn cpp_parallel(mut self, name: &str, chart_line_style: u32, point_type: Option<u32>, cpp_name: &str) -> Self {
+    if !self.settings.cpp_enabled || !self.settings.includes_algorithm(name) { return self; }
+
+    let size: usize = self.shape.iter().product();
+    let row_length = self.shape.last().copied().unwrap_or(size);
+    let row_count = size / row_length.max(1);
+
+    println!("{}", name);
+    let mut series = Series{ name: name.to_owned(), chart_line_style, point_type, ours: false, speedups: vec![], times: vec![], samples: vec![], statistics: vec![], counters: vec![], bandwidths: vec![], lookback: vec![] };
+    for &thread_count in &self.settings.thread_counts {
+      if thread_count > self.max_threads as usize {
+        break;
+      }
+
+      let child = std::process::Command::new("./reference-cpp/build/main")
+        .env("LD_LIBRARY_PATH", "./reference-cpp/oneTBB-install/lib")
+        .arg(cpp_name)
+        .arg(size.to_string())
+        .arg(row_length.to_string())
+        .arg(row_count.to_string())
+        .arg(thread_count.to_string())
+        .output()
+        .expect("Reference parallel C++ implementation failed");
+
+      let output = String::from_utf8_lossy(&child.stdout);
+      if !child.status.success() {
+        println!("  {}", output.trim());
+        return self;
+      }
+      // The C++ program prints the time of every run in nanoseconds
+      let samples: Vec<u64> = output.split_whitespace().map(|sample| sample.parse().expect("Unexpected output from reference C++ program")).collect();
+      let time = mean_micros(&samples);
+      let statistics = Statistics::new(&samples);
       let relative = self.reference_time as f32 / time as f32;
-      results.push(relative);
-      println!("  {:02} threads {} ms ({:.2}x)", thread_count, time / 1000, relative);
+      let bandwidth = achieved_bandwidth(self.shape.iter().product(), self.bytes_per_element, statistics.mean);
+      let peak = if self.settings.bandwidth { Some(stream::peak_bandwidth(thread_count)) } else { None };
+      println!("  {:02} threads {} ({:.2}x, {})", thread_count, format_statistics(&statistics), relative, format_bandwidth(bandwidth, peak));
+
+      series.speedups.push(relative);
+      series.times.push(time);
+      series.samples.push(samples);
+      series.statistics.push(statistics);
+      series.counters.push(None);
+      series.bandwidths.push(bandwidth);
+      series.lookback.push(None);
     }
-    self.output.push((name.to_owned(), chart_line_style, point_type, our, results));
+    self.output.push(series);
     self
   }
 
-  pub fn cpp_sequential(mut self, cpp_enabled: bool, name: &str, cpp_name: &str, size: usize, row_length: usize, row_count: usize) -> Self {
-    if !cpp_enabled { return self; }
+  pub fn cpp_sequential(mut self, name: &str, cpp_name: &str, size: usize, row_length: usize, row_count: usize) -> Self {
+    if !self.settings.cpp_enabled { return self; }
 
     let mut command = std::process::Command::new("./reference-cpp/build/main");
     command.env("LD_LIBRARY_PATH", "./reference-cpp/oneTBB-install/lib")
@@ -85,10 +353,99 @@ impl<T: Copy + Debug + Eq + Send> Benchmarker<T> {
   }
 }
 
+impl<T> Benchmarker<T> {
+  fn write_json(&self, filename: &str) {
+    let metadata = metadata::get();
+    let file = File::create(filename.to_owned() + ".json").unwrap();
+    let mut writer = BufWriter::new(&file);
+
+    writeln!(&mut writer, "{{").unwrap();
+    writeln!(&mut writer, "  \"benchmark\": {},", json_string(&self.name)).unwrap();
+    writeln!(&mut writer, "  \"shape\": [{}],", join(&self.shape, ", ")).unwrap();
+    writeln!(&mut writer, "  \"runs\": {},", self.settings.runs).unwrap();
+    writeln!(&mut writer, "  \"metadata\": {{").unwrap();
+    writeln!(&mut writer, "    \"host\": {},", json_string(&metadata.host)).unwrap();
+    writeln!(&mut writer, "    \"cpu_model\": {},", json_string(&metadata.cpu_model)).unwrap();
+    writeln!(&mut writer, "    \"cpu_count\": {},", metadata.cpu_count).unwrap();
+    writeln!(&mut writer, "    \"os\": {},", json_string(&metadata.os)).unwrap();
+    writeln!(&mut writer, "    \"git_revision\": {},", json_string(&metadata.git_revision)).unwrap();
+    writeln!(&mut writer, "    \"git_dirty\": {},", metadata.git_dirty).unwrap();
+    writeln!(&mut writer, "    \"build_profile\": {},", json_string(metadata.build_profile)).unwrap();
+    writeln!(&mut writer, "    \"block_size\": {},", metadata.block_size).unwrap();
+    writeln!(&mut writer, "    \"huge_pages\": {},", json_string(huge_pages::mode().name())).unwrap();
+    writeln!(&mut writer, "    \"timestamp\": {}", metadata.timestamp).unwrap();
+    writeln!(&mut writer, "  }},").unwrap();
+    let measured = stream::measured();
+    let stream_json: Vec<String> = measured.iter()
+      .map(|(thread_count, bandwidth)| format!("{{ \"threads\": {}, \"copy_gbs\": {}, \"triad_gbs\": {} }}", thread_count, json_number64(bandwidth.copy), json_number64(bandwidth.triad)))
+      .collect();
+    writeln!(&mut writer, "  \"stream\": [{}],", stream_json.join(", ")).unwrap();
+    writeln!(&mut writer, "  \"bytes_per_element\": {},", json_number64(self.bytes_per_element)).unwrap();
+    let elements = self.shape.iter().product();
+    writeln!(&mut writer, "  \"sequential\": {{ \"time_us\": {}, \"statistics_ns\": {}, \"counters\": {}, \"samples_ns\": [{}] }},", self.reference_time, json_statistics(&self.reference_statistics), json_counters(&self.reference_counters, elements, self.reference_statistics.mean), join(&self.reference_samples, ", ")).unwrap();
+    match self.reference_time_cpp {
+      Some(time) => writeln!(&mut writer, "  \"sequential_cpp\": {{ \"time_us\": {} }},", time).unwrap(),
+      None => writeln!(&mut writer, "  \"sequential_cpp\": null,").unwrap()
+    }
+    writeln!(&mut writer, "  \"algorithms\": [").unwrap();
+    for (idx, result) in self.output.iter().enumerate() {
+      writeln!(&mut writer, "    {{").unwrap();
+      writeln!(&mut writer, "      \"name\": {},", json_string(&result.name)).unwrap();
+      writeln!(&mut writer, "      \"ours\": {},", result.ours).unwrap();
+      writeln!(&mut writer, "      \"results\": [").unwrap();
+      for (thread_idx, samples) in result.samples.iter().enumerate() {
+        let thread_count = self.settings.thread_counts[thread_idx];
+        let bandwidth = result.bandwidths[thread_idx];
+        let fraction = match measured.iter().find(|(count, _)| *count == thread_count) {
+          Some((_, peak)) => json_number64(bandwidth / peak.copy),
+          None => "null".to_owned()
+        };
+        write!(&mut writer, "        {{ \"threads\": {}, \"time_us\": {}, \"speedup\": {}, \"bandwidth_gbs\": {}, \"fraction_of_copy_bandwidth\": {}, \"statistics_ns\": {}, \"counters\": {}, \"lookback\": {}, \"samples_ns\": [{}] }}",
+          thread_count, result.times[thread_idx], json_number(result.speedups[thread_idx]), json_number64(bandwidth), fraction, json_statistics(&result.statistics[thread_idx]),
+          json_counters(&result.counters[thread_idx], elements, result.statistics[thread_idx].mean), json_lookback(&result.lookback[thread_idx]), join(samples, ", ")).unwrap();
+        writeln!(&mut writer, "{}", if thread_idx + 1 < result.samples.len() { "," } else { "" }).unwrap();
+      }
+      writeln!(&mut writer, "      ]").unwrap();
+      writeln!(&mut writer, "    }}{}", if idx + 1 < self.output.len() { "," } else { "" }).unwrap();
+    }
+    writeln!(&mut writer, "  ]").unwrap();
+    writeln!(&mut writer, "}}").unwrap();
+  }
+
+  fn write_csv(&self, filename: &str) {
+    let metadata = metadata::get();
+    let file = File::create(filename.to_owned() + ".csv").unwrap();
+    let mut writer = BufWriter::new(&file);
+
+    // One row per run. The C++ implementation only reports its average time.
+    writeln!(&mut writer, "benchmark,shape,algorithm,threads,run,time_ns,mean_time_us,speedup,host,cpu_model,cpu_count,block_size,git_revision,build_profile,timestamp").unwrap();
+    let shape = join(&self.shape, "x");
+    let suffix = format!("{},{},{},{},{},{},{}",
+      csv_field(&metadata.host), csv_field(&metadata.cpu_model), metadata.cpu_count, metadata.block_size,
+      csv_field(&metadata.git_revision), metadata.build_profile, metadata.timestamp);
+    let prefix = csv_field(&self.name) + "," + &shape;
+
+    for (run, sample) in self.reference_samples.iter().enumerate() {
+      writeln!(&mut writer, "{},Sequential (Rust),1,{},{},{},1,{}", prefix, run, sample, self.reference_time, suffix).unwrap();
+    }
+    if let Some(time) = self.reference_time_cpp {
+      writeln!(&mut writer, "{},Sequential (C++),1,,,{},{},{}", prefix, time, self.reference_time as f32 / time as f32, suffix).unwrap();
+    }
+    for result in &self.output {
+      for (thread_idx, samples) in result.samples.iter().enumerate() {
+        for (run, sample) in samples.iter().enumerate() {
+          writeln!(&mut writer, "{},{},{},{},{},{},{},{}", prefix, csv_field(&result.name), self.settings.thread_counts[thread_idx], run, sample, result.times[thread_idx], result.speedups[thread_idx], suffix).unwrap();
+        }
+      }
+    }
+  }
+}
+
 impl<T> Drop for Benchmarker<T> {
   fn drop(&mut self) {
-    std::fs::create_dir_all("./results-intel-12900").unwrap();
-    let filename = "./results-intel-12900/".to_owned() + &self.name.replace(' ', "_").replace('(', "").replace(')', "").replace('=', "_").replace('/', "_");
+    std::fs::create_dir_all(&self.settings.output_dir).unwrap();
+    let filename = self.settings.output_dir.clone() + "/" + &artifact_name(&self.name);
+    write_index(&self.settings.output_dir);
 
     // Create .gnuplot file
     let file_gnuplot = File::create(filename.clone() + ".gnuplot").unwrap();
@@ -113,8 +470,8 @@ impl<T> Drop for Benchmarker<T> {
       if idx != 0 {
         write!(&mut writer_gnuplot, ", \\\n  ").unwrap();
       }
-      write!(&mut writer_gnuplot, "'{}.dat' using 1:{} title \"{}\" ls {} lw 1 pointsize {}", filename, idx+2, result.0, result.1, if result.3 { 0.7 } else { 0.6 }).unwrap();
-      if let Some(point_type) = result.2 {
+      write!(&mut writer_gnuplot, "'{}.dat' using 1:{} title \"{}\" ls {} lw 1 pointsize {}", filename, idx+2, result.name, result.chart_line_style, if result.ours { 0.7 } else { 0.6 }).unwrap();
+      if let Some(point_type) = result.point_type {
         write!(&mut writer_gnuplot, " pointtype {}", point_type).unwrap();
       }
       write!(&mut writer_gnuplot, " with linespoints").unwrap();
@@ -131,15 +488,15 @@ impl<T> Drop for Benchmarker<T> {
     // Header
     write!(&mut writer_data, "# NCPU").unwrap();
     for result in &self.output {
-      write!(&mut writer_data, "\t{}", result.0).unwrap();
+      write!(&mut writer_data, "\t{}", result.name).unwrap();
     }
     write!(&mut writer_data, "\n").unwrap();
 
-    for (idx, thread_count) in THREAD_COUNTS.iter().enumerate() {
+    for (idx, thread_count) in self.settings.thread_counts.iter().enumerate() {
       write!(&mut writer_data, "{}", thread_count).unwrap();
       for result in &self.output {
-        if idx < result.4.len() {
-          write!(&mut writer_data, "\t{}", result.4[idx]).unwrap();
+        if idx < result.speedups.len() {
+          write!(&mut writer_data, "\t{}", result.speedups[idx]).unwrap();
         } else {
           write!(&mut writer_data, "\t").unwrap();
         }
@@ -148,21 +505,54 @@ impl<T> Drop for Benchmarker<T> {
     }
     drop(writer_data);
 
-    _ = std::process::Command::new("gnuplot")
-      .arg(filename.clone() + ".gnuplot")
-      .spawn();
+    if std::process::Command::new("gnuplot").arg(filename.clone() + ".gnuplot").spawn().is_err() && !GNUPLOT_WARNED.swap(true, Ordering::Relaxed) {
+      println!("Could not run gnuplot, only SVG charts are generated.");
+    }
 
-    // Create .tex file with table of results
-    let file_tex = File::create(filename.clone() + ".tex").unwrap();
-    let mut writer_tex = BufWriter::new(&file_tex);
+    // Create .svg chart and the HTML report of all benchmarks in this run, which don't require gnuplot
+    let chart = Chart{
+      title: &self.name,
+      with_key: self.chart_style == ChartStyle::WithKey,
+      max_threads: self.max_threads,
+      max_speedup: self.max_speedup,
+      lines: self.output.iter().map(|result| Line{
+        name: &result.name,
+        line_style: result.chart_line_style,
+        point_type: result.point_type,
+        ours: result.ours,
+        points: self.settings.thread_counts.iter().copied().zip(result.speedups.iter().copied()).collect()
+      }).collect()
+    };
+    std::fs::write(filename.clone() + ".svg", chart::svg(&chart)).unwrap();
+    write_report(&self.settings.output_dir);
+
+    // Machine-readable results, including the raw timings and metadata
+    self.write_json(&filename);
+    self.write_csv(&filename);
+    if !self.traces.is_empty() {
+      trace::write_chrome_trace(&(filename.clone() + ".trace.json"), &self.traces);
+    }
 
     // Don't show high thread counts or thread counts between 8 and 16, as the results don't change that much there.
-    let table_thread_counts: Vec<usize> =
+    let preferred_thread_counts: Vec<usize> =
       if self.max_threads == 24 {
         vec![1, 2, 3, 4, 8, 16, 24]
       } else {
         vec![1, 2, 3, 4, 6, 8, 16]
       };
+    // Only show thread counts that were measured in this run.
+    let mut table_thread_counts: Vec<usize> = preferred_thread_counts.into_iter().filter(|thread_count| self.settings.thread_counts.contains(thread_count)).collect();
+    if table_thread_counts.len() < 2 {
+      table_thread_counts = self.settings.thread_counts.clone();
+    }
+    if table_thread_counts.len() < 2 {
+      // The table requires at least two columns.
+      return;
+    }
+
+    // Create .tex file with table of results
+    let file_tex = File::create(filename.clone() + ".tex").unwrap();
+    let mut writer_tex = BufWriter::new(&file_tex);
 
     // Note that { is escaped as {{ in Rust, } as }} and \ as \\.
 
@@ -192,17 +582,17 @@ impl<T> Drop for Benchmarker<T> {
 
     // Parallel times
     for result in &self.output {
-      let color_factor = if result.3 { 30 } else { 10 };
-      write!(&mut writer_tex, "\\rowcolor{{gnuplot{}!{}}}", result.1, color_factor).unwrap();
-      write!(&mut writer_tex, "$\\color{{gnuplot{}}}{{{}}}$", result.1, latex_symbol(result)).unwrap();
-      if result.3 {
-        write!(&mut writer_tex, " & \\textit{{{}}}", result.0).unwrap();
+      let color_factor = if result.ours { 30 } else { 10 };
+      write!(&mut writer_tex, "\\rowcolor{{gnuplot{}!{}}}", result.chart_line_style, color_factor).unwrap();
+      write!(&mut writer_tex, "$\\color{{gnuplot{}}}{{{}}}$", result.chart_line_style, latex_symbol(result)).unwrap();
+      if result.ours {
+        write!(&mut writer_tex, " & \\textit{{{}}}", result.name).unwrap();
       } else {
-        write!(&mut writer_tex, " & {}", result.0).unwrap();
+        write!(&mut writer_tex, " & {}", result.name).unwrap();
       }
-      for (idx, &value) in result.4.iter().enumerate() {
-        if table_thread_counts.contains(&THREAD_COUNTS[idx]) {
-          write!(&mut writer_tex, " & \\cellcolor{{gnuplot{}!{}}} {:.2}", result.1, color_factor, value).unwrap();
+      for (idx, &value) in result.speedups.iter().enumerate() {
+        if table_thread_counts.contains(&self.settings.thread_counts[idx]) {
+          write!(&mut writer_tex, " & \\cellcolor{{gnuplot{}!{}}} {:.2}", result.chart_line_style, color_factor, value).unwrap();
         }
       }
       write!(&mut writer_tex, " \\\\\n").unwrap();
@@ -213,24 +603,139 @@ impl<T> Drop for Benchmarker<T> {
   }
 }
 
-pub fn time<T: Debug + Eq, P: FnMut() -> (), F: FnMut() -> T>(runs: usize, mut prepare: P, mut f: F) -> (T, u64) {
+// Returns the result of f and the average time of a run in microseconds.
+pub fn time<T: Debug + Eq, P: FnMut() -> (), F: FnMut() -> T>(runs: usize, prepare: P, f: F) -> (T, u64) {
+  let (first, samples) = time_samples(runs, prepare, f);
+  (first, mean_micros(&samples))
+}
+
+// Returns the result of f and the time of each run in nanoseconds.
+pub fn time_samples<T: Debug + Eq, P: FnMut(), F: FnMut() -> T>(runs: usize, mut prepare: P, mut f: F) -> (T, Vec<u64>) {
   prepare();
   let first = f();
-  
-  let mut elapsed = std::time::Duration::ZERO;
+  // Don't include the warm-up run in the performance counters
+  perf::reset();
+
+  let mut samples = Vec::with_capacity(runs);
   for _ in 0 .. runs {
     prepare();
+    let counters = perf::start_run();
+    let timer = time::Instant::now();
+    let result = f();
+    samples.push(timer.elapsed().as_nanos() as u64);
+    perf::finish_run(counters);
+    assert_eq!(first, result);
+  }
+
+  (first, samples)
+}
+
+// Measures f with the number of runs configured in settings.
+pub fn measure<T: Debug + Eq, P: FnMut(), F: FnMut() -> T>(settings: &Settings, prepare: P, f: F) -> (T, Vec<u64>) {
+  match settings.precision {
+    None => time_samples(settings.runs, prepare, f),
+    Some(precision) => time_adaptive(settings.runs, settings.max_runs, precision, prepare, f)
+  }
+}
+
+// Performs at least min_runs runs, and continues until the confidence interval of the mean
+// is tight enough (relative to the mean) or max_runs is reached.
+pub fn time_adaptive<T: Debug + Eq, P: FnMut(), F: FnMut() -> T>(min_runs: usize, max_runs: usize, precision: f64, mut prepare: P, mut f: F) -> (T, Vec<u64>) {
+  let (first, mut samples) = time_samples(min_runs.max(2), &mut prepare, &mut f);
+
+  while samples.len() < max_runs && Statistics::new(&samples).relative_ci() > precision {
+    prepare();
+    let counters = perf::start_run();
     let timer = time::Instant::now();
     let result = f();
-    elapsed += timer.elapsed();
+    samples.push(timer.elapsed().as_nanos() as u64);
+    perf::finish_run(counters);
     assert_eq!(first, result);
   }
 
-  (first, (elapsed.as_micros() / runs as u128) as u64)
+  (first, samples)
+}
+
+// Bandwidth in GB/s (bytes per nanosecond)
+fn achieved_bandwidth(elements: usize, bytes_per_element: f64, time_ns: f64) -> f64 {
+  elements as f64 * bytes_per_element / time_ns
+}
+
+// Formats the achieved bandwidth, and the fraction of the STREAM copy bandwidth with the same number of threads
+fn format_bandwidth(bandwidth: f64, peak: Option<Bandwidth>) -> String {
+  match peak {
+    Some(peak) => format!("{:.1} GB/s, {:.0}% of copy bandwidth", bandwidth, 100.0 * bandwidth / peak.copy),
+    None => format!("{:.1} GB/s", bandwidth)
+  }
+}
+
+// Formats the counters of a run relative to the number of elements.
+// The memory traffic is estimated as one cache line per last-level cache miss.
+fn format_counters(counters: &Counters, elements: usize, time_ns: f64) -> String {
+  let per_element = |name: &str| counters.get(name).map(|value| value as f64 / elements.max(1) as f64);
+  let mut parts = vec![];
+  if let Some(cycles) = per_element("cycles") {
+    parts.push(format!("{:.2} cycles/element", cycles));
+  }
+  if let (Some(instructions), Some(cycles)) = (counters.get("instructions"), counters.get("cycles")) {
+    parts.push(format!("IPC {:.2}", instructions as f64 / cycles.max(1) as f64));
+  }
+  if let Some(misses) = per_element("cache-misses") {
+    parts.push(format!("{:.4} cache misses/element", misses));
+  }
+  if let Some(misses) = per_element("branch-misses") {
+    parts.push(format!("{:.4} branch misses/element", misses));
+  }
+  if let Some(bandwidth) = memory_bandwidth(counters, time_ns) {
+    parts.push(format!("~{:.2} GB/s memory traffic", bandwidth));
+  }
+  parts.join(", ")
+}
+
+// Estimated memory bandwidth in GB/s, based on the last-level cache misses
+fn memory_bandwidth(counters: &Counters, time_ns: f64) -> Option<f64> {
+  counters.get("cache-misses").map(|misses| (misses * perf::CACHE_LINE_SIZE) as f64 / time_ns)
+}
+
+fn json_counters(counters: &Option<Counters>, elements: usize, time_ns: f64) -> String {
+  let Some(counters) = counters else { return "null".to_owned(); };
+  let mut fields: Vec<String> = perf::EVENTS.iter()
+    .filter_map(|(name, _)| counters.get(name).map(|value| format!("{}: {}", json_string(name), value)))
+    .collect();
+  fields.push(format!("\"elements\": {}", elements));
+  if let Some(bandwidth) = memory_bandwidth(counters, time_ns) {
+    fields.push(format!("\"memory_bandwidth_gbs\": {}", json_number64(bandwidth)));
+  }
+  "{ ".to_owned() + &fields.join(", ") + " }"
+}
+
+// Formats the look-back statistics of a run: the fraction of blocks that were scanned directly,
+// the average and maximum number of visited predecessors per look-back and the waiting iterations.
+fn format_lookback(statistics: &LookbackAverages) -> String {
+  let average = statistics.visited / statistics.lookbacks.max(1.0);
+  format!("look-back: {:.1}% of {:.1} blocks immediate, {:.1} look-backs visiting {:.2} blocks on average (max {}), {:.1} spins",
+    100.0 * statistics.immediate / statistics.blocks().max(1.0), statistics.blocks(), statistics.lookbacks, average, statistics.max_length, statistics.spins)
+}
+
+fn json_lookback(statistics: &Option<LookbackAverages>) -> String {
+  let Some(statistics) = statistics else { return "null".to_owned(); };
+  format!("{{ \"immediate\": {}, \"lookbacks\": {}, \"visited\": {}, \"max_length\": {}, \"spins\": {} }}",
+    json_number64(statistics.immediate), json_number64(statistics.lookbacks), json_number64(statistics.visited), statistics.max_length, json_number64(statistics.spins))
+}
+
+fn format_statistics(statistics: &Statistics) -> String {
+  let ms = |nanoseconds: f64| nanoseconds / 1_000_000.0;
+  format!("{:.3} ms ± {:.3} (min {:.3}, median {:.3}, p90 {:.3}, p99 {:.3}, sd {:.3}, {} runs, {} outliers)",
+    ms(statistics.mean), ms(statistics.mean - statistics.ci_low), ms(statistics.min), ms(statistics.median),
+    ms(statistics.p90), ms(statistics.p99), ms(statistics.std_dev), statistics.count, statistics.outliers)
+}
+
+fn mean_micros(samples: &[u64]) -> u64 {
+  samples.iter().sum::<u64>() / 1000 / samples.len() as u64
 }
 
-fn latex_symbol(result: &(String, u32, Option<u32>, bool, Vec<f32>)) -> &str {
-  match result.1 {
+fn latex_symbol(result: &Series) -> &str {
+  match result.chart_line_style {
     3 => "\\smallblackdiamond",
     5 => "\\blacksquare",
     7 => "\\scalebox{1.1}{$\\bullet$}",
@@ -242,3 +747,47 @@ fn latex_symbol(result: &(String, u32, Option<u32>, bool, Vec<f32>)) -> &str {
     _ => "?"
   }
 }
+
+fn join<V: ToString>(values: &[V], separator: &str) -> String {
+  values.iter().map(|value| value.to_string()).collect::<Vec<String>>().join(separator)
+}
+
+fn json_string(value: &str) -> String {
+  let mut result = String::with_capacity(value.len() + 2);
+  result.push('"');
+  for c in value.chars() {
+    match c {
+      '"' => result.push_str("\\\""),
+      '\\' => result.push_str("\\\\"),
+      '\n' => result.push_str("\\n"),
+      c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+      c => result.push(c)
+    }
+  }
+  result.push('"');
+  result
+}
+
+fn json_statistics(statistics: &Statistics) -> String {
+  format!("{{ \"mean\": {}, \"min\": {}, \"max\": {}, \"median\": {}, \"p90\": {}, \"p99\": {}, \"std_dev\": {}, \"ci95_low\": {}, \"ci95_high\": {}, \"outliers\": {} }}",
+    json_number64(statistics.mean), json_number64(statistics.min), json_number64(statistics.max), json_number64(statistics.median),
+    json_number64(statistics.p90), json_number64(statistics.p99), json_number64(statistics.std_dev),
+    json_number64(statistics.ci_low), json_number64(statistics.ci_high), statistics.outliers)
+}
+
+fn json_number64(value: f64) -> String {
+  if value.is_finite() { value.to_string() } else { "null".to_owned() }
+}
+
+// JSON has no representation for infinity or NaN
+fn json_number(value: f32) -> String {
+  if value.is_finite() { value.to_string() } else { "null".to_owned() }
+}
+
+fn csv_field(value: &str) -> String {
+  if value.contains(',') || value.contains('"') || value.contains('\n') {
+    "\"".to_owned() + &value.replace('"', "\"\"") + "\""
+  } else {
+    value.to_owned()
+  }
+}
@@ -0,0 +1,153 @@
+use std::fmt::Write;
+
+// Renderer of speedup charts as SVG, which doesn't require gnuplot.
+// The line styles and point types follow the default line types of gnuplot,
+// such that the charts look like the PDFs generated with gnuplot.
+
+pub struct Chart<'a> {
+  pub title: &'a str,
+  pub with_key: bool,
+  pub max_threads: u32,
+  pub max_speedup: u32,
+  pub lines: Vec<Line<'a>>,
+}
+
+pub struct Line<'a> {
+  pub name: &'a str,
+  pub line_style: u32,
+  pub point_type: Option<u32>,
+  pub ours: bool,
+  // Thread count and speedup
+  pub points: Vec<(usize, f32)>,
+}
+
+// Colors of the default line types of gnuplot, starting at line type 1
+const COLORS: [&str; 8] = ["#9400d3", "#009e73", "#56b4e9", "#e69f00", "#f0e442", "#0072b2", "#e51e10", "#000000"];
+
+const WIDTH: f32 = 480.0;
+const HEIGHT: f32 = 420.0;
+const MARGIN_LEFT: f32 = 60.0;
+const MARGIN_RIGHT: f32 = 15.0;
+const MARGIN_TOP: f32 = 35.0;
+const MARGIN_BOTTOM: f32 = 50.0;
+// Size in pixels of a point with pointsize 1
+const POINT_SIZE: f32 = 9.0;
+
+pub fn svg(chart: &Chart) -> String {
+  let plot_width = WIDTH - MARGIN_LEFT - MARGIN_RIGHT;
+  let plot_height = HEIGHT - MARGIN_TOP - MARGIN_BOTTOM;
+  let max_threads = chart.max_threads.max(2) as f32;
+  let max_speedup = chart.max_speedup.max(1) as f32;
+  let x = |thread_count: f32| MARGIN_LEFT + (thread_count - 1.0) / (max_threads - 1.0) * plot_width;
+  let y = |speedup: f32| MARGIN_TOP + (1.0 - speedup / max_speedup) * plot_height;
+
+  let mut out = String::new();
+  writeln!(out, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" font-family=\"sans-serif\" font-size=\"12\">", WIDTH, HEIGHT, WIDTH, HEIGHT).unwrap();
+  writeln!(out, "<rect width=\"100%\" height=\"100%\" fill=\"white\"/>").unwrap();
+  writeln!(out, "<defs><clipPath id=\"plot\"><rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/></clipPath></defs>", MARGIN_LEFT, MARGIN_TOP, plot_width, plot_height).unwrap();
+  writeln!(out, "<text x=\"{}\" y=\"20\" text-anchor=\"middle\" font-size=\"14\">{}</text>", WIDTH / 2.0, escape(chart.title)).unwrap();
+
+  // Axes and tics, similar to the tics in the gnuplot file
+  for thread_count in [1, 4, 8, 12, 16, 20, 24, 28, 32] {
+    if thread_count as f32 > max_threads {
+      break;
+    }
+    let position = x(thread_count as f32);
+    writeln!(out, "<line x1=\"{0:.1}\" y1=\"{1:.1}\" x2=\"{0:.1}\" y2=\"{2:.1}\" stroke=\"black\"/>", position, MARGIN_TOP + plot_height, MARGIN_TOP + plot_height - 5.0).unwrap();
+    writeln!(out, "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\">{}</text>", position, MARGIN_TOP + plot_height + 16.0, thread_count).unwrap();
+  }
+  let step = if max_speedup <= 4.0 { 0.5 } else if max_speedup <= 10.0 { 1.0 } else { (max_speedup / 10.0).ceil() };
+  let mut speedup = 0.0;
+  while speedup <= max_speedup {
+    let position = y(speedup);
+    writeln!(out, "<line x1=\"{0:.1}\" y1=\"{1:.1}\" x2=\"{2:.1}\" y2=\"{1:.1}\" stroke=\"black\"/>", MARGIN_LEFT, position, MARGIN_LEFT + 5.0).unwrap();
+    writeln!(out, "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\">{}</text>", MARGIN_LEFT - 6.0, position + 4.0, speedup).unwrap();
+    speedup += step;
+  }
+  writeln!(out, "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"none\" stroke=\"black\"/>", MARGIN_LEFT, MARGIN_TOP, plot_width, plot_height).unwrap();
+  writeln!(out, "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\">Number of threads</text>", MARGIN_LEFT + plot_width / 2.0, HEIGHT - 12.0).unwrap();
+  writeln!(out, "<text transform=\"translate(16 {}) rotate(-90)\" text-anchor=\"middle\">Speedup</text>", MARGIN_TOP + plot_height / 2.0).unwrap();
+
+  // Data
+  writeln!(out, "<g clip-path=\"url(#plot)\">").unwrap();
+  for line in &chart.lines {
+    let color = color(line.line_style);
+    let size = POINT_SIZE * if line.ours { 0.7 } else { 0.6 };
+    let coordinates: Vec<String> = line.points.iter().map(|&(thread_count, speedup)| format!("{:.1},{:.1}", x(thread_count as f32), y(speedup))).collect();
+    writeln!(out, "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"1\"/>", coordinates.join(" "), color).unwrap();
+    for &(thread_count, speedup) in &line.points {
+      point(&mut out, x(thread_count as f32), y(speedup), line.point_type.unwrap_or(line.line_style), size, color);
+    }
+  }
+  writeln!(out, "</g>").unwrap();
+
+  // Key in the bottom right corner, like 'set key bottom right Right'
+  if chart.with_key {
+    let sample_end = MARGIN_LEFT + plot_width - 10.0;
+    let sample_start = sample_end - 30.0;
+    for (idx, line) in chart.lines.iter().rev().enumerate() {
+      let position = MARGIN_TOP + plot_height - 12.0 - idx as f32 * 16.0;
+      let color = color(line.line_style);
+      writeln!(out, "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\">{}</text>", sample_start - 6.0, position + 4.0, escape(line.name)).unwrap();
+      writeln!(out, "<line x1=\"{0:.1}\" y1=\"{2:.1}\" x2=\"{1:.1}\" y2=\"{2:.1}\" stroke=\"{3}\"/>", sample_start, sample_end, position, color).unwrap();
+      let size = POINT_SIZE * if line.ours { 0.7 } else { 0.6 };
+      point(&mut out, (sample_start + sample_end) / 2.0, position, line.point_type.unwrap_or(line.line_style), size, color);
+    }
+  }
+
+  writeln!(out, "</svg>").unwrap();
+  out
+}
+
+fn color(line_style: u32) -> &'static str {
+  COLORS[(line_style.max(1) as usize - 1) % COLORS.len()]
+}
+
+// Draws a point with the shape of a gnuplot point type:
+// 1 plus, 2 cross, 3 star, 4/5 square, 6/7 circle, 8/9 triangle, 10/11 inverted triangle,
+// 12/13 diamond and 14/15 pentagon, where the odd point types (from 5) are filled.
+fn point(out: &mut String, x: f32, y: f32, point_type: u32, size: f32, color: &str) {
+  let r = size / 2.0;
+  let point_type = (point_type.max(1) - 1) % 15 + 1;
+  let fill = if point_type >= 5 && point_type % 2 == 1 { color } else { "none" };
+  let polygon = |out: &mut String, corners: &[(f32, f32)]| {
+    let coordinates: Vec<String> = corners.iter().map(|(dx, dy)| format!("{:.1},{:.1}", x + dx * r, y + dy * r)).collect();
+    writeln!(out, "<polygon points=\"{}\" fill=\"{}\" stroke=\"{}\"/>", coordinates.join(" "), fill, color).unwrap();
+  };
+  let segment = |out: &mut String, dx: f32, dy: f32| {
+    writeln!(out, "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"{}\"/>", x - dx * r, y - dy * r, x + dx * r, y + dy * r, color).unwrap();
+  };
+  match point_type {
+    1 => { segment(out, 1.0, 0.0); segment(out, 0.0, 1.0); },
+    2 => { segment(out, 0.7, 0.7); segment(out, 0.7, -0.7); },
+    3 => { segment(out, 1.0, 0.0); segment(out, 0.0, 1.0); segment(out, 0.7, 0.7); segment(out, 0.7, -0.7); },
+    4 | 5 => polygon(out, &[(-0.8, -0.8), (0.8, -0.8), (0.8, 0.8), (-0.8, 0.8)]),
+    6 | 7 => writeln!(out, "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{:.1}\" fill=\"{}\" stroke=\"{}\"/>", x, y, r, fill, color).unwrap(),
+    8 | 9 => polygon(out, &[(0.0, -1.0), (0.9, 0.6), (-0.9, 0.6)]),
+    10 | 11 => polygon(out, &[(0.0, 1.0), (0.9, -0.6), (-0.9, -0.6)]),
+    12 | 13 => polygon(out, &[(0.0, -1.0), (1.0, 0.0), (0.0, 1.0), (-1.0, 0.0)]),
+    _ => polygon(out, &[(0.0, -1.0), (0.95, -0.31), (0.59, 0.81), (-0.59, 0.81), (-0.95, -0.31)])
+  }
+}
+
+// Combines the SVG charts of several benchmarks in a single HTML page.
+// Every entry consists of the benchmark name and its SVG chart.
+pub fn html_report(title: &str, charts: &[(String, String)]) -> String {
+  let mut out = String::new();
+  writeln!(out, "<!DOCTYPE html>").unwrap();
+  writeln!(out, "<html><head><meta charset=\"utf-8\"><title>{}</title>", escape(title)).unwrap();
+  writeln!(out, "<style>body {{ font-family: sans-serif; }} figure {{ display: inline-block; margin: 8px; }}</style>").unwrap();
+  writeln!(out, "</head><body>").unwrap();
+  writeln!(out, "<h1>{}</h1>", escape(title)).unwrap();
+  for (idx, (name, svg)) in charts.iter().enumerate() {
+    // Ids must be unique within the page
+    let svg = svg.replace("id=\"plot\"", &format!("id=\"plot-{}\"", idx)).replace("url(#plot)", &format!("url(#plot-{})", idx));
+    writeln!(out, "<figure>{}<figcaption>{}</figcaption></figure>", svg, escape(name)).unwrap();
+  }
+  writeln!(out, "</body></html>").unwrap();
+  out
+}
+
+fn escape(text: &str) -> String {
+  text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
@@ -0,0 +1,250 @@
+use crate::utils::benchmark::{algorithm_id, Settings};
+use crate::utils::huge_pages::HugePages;
+use crate::utils::numa::Placement;
+use crate::utils::thread_pinning::AFFINITY_MAPPING;
+
+// Command-line interface of the benchmark driver.
+// Options can be passed as '--option value' or as '--option=value'.
+// Options can also be read from a config file with one 'option = value' (or just 'option' for flags) per line.
+
+pub const BENCHMARKS: [&str; 19] = ["row-vs-column", "higherdim", "fourdim", "scan", "scan-multidim", "inplace", "inplace-multidim", "compact", "compact-multidim", "radix-sort", "partition", "partition-multidim", "recurrence", "rle", "rle-multidim", "reduce", "reduce-multidim", "histogram", "histogram-multidim"];
+
+pub const USAGE: &str = "Usage: workstealing [options]
+
+Options:
+  --benchmarks <list>   Comma-separated benchmarks to run (default: all)
+                        row-vs-column, higherdim, fourdim, scan, scan-multidim, inplace, inplace-multidim,
+                        compact, compact-multidim, radix-sort, partition, partition-multidim, recurrence,
+                        rle, rle-multidim, reduce, reduce-multidim, histogram, histogram-multidim
+  --sizes <list>        Comma-separated input sizes of the one-dimensional benchmarks
+  --shapes <list>       Comma-separated shapes of the multidimensional benchmarks, of any number of dimensions,
+                        e.g. 10000x10000,100x100x10000
+  --algorithms <list>   Comma-separated parallel implementations to run (default: all), e.g. column-wise-chained
+  --threads <list>      Comma-separated thread counts (default: 1,2,3,4,6,8,10,12,14,16)
+  --runs <n>            Number of timed runs per measurement (default: 50), or the minimum if --precision is set
+  --precision <f>       Repeat runs until the 95% confidence interval of the mean is within this fraction
+                        of the mean, e.g. 0.01 for 1%
+  --max-runs <n>        Maximum number of runs per measurement when --precision is set (default: 1000)
+  --output-dir <dir>    Directory for the generated charts and tables (default: ./results-<cpu model>)
+  --cpp                 Build and run the C++ reference implementation without asking
+  --no-cpp              Don't build or run the C++ reference implementation
+  --config <file>       Read options from a file, containing lines like 'shapes = 10000x10000'
+  --baseline <dir>      Compare the results in the output directory against a previous result set,
+                        and exit with a non-zero status if a cell regressed
+  --threshold <f>       Relative slowdown that counts as a regression, e.g. 0.05 for 5% (default: 0.05)
+  --compare-only        Compare the existing results in the output directory without running benchmarks
+  --numa <placement>    Placement of the pages of the arrays over the NUMA nodes (default: first-touch)
+                        first-touch: on the node of the worker that fills them, with workers preferring the
+                        blocks on their own node; interleave: round-robin over all nodes, without preference;
+                        local: bound to the node of the workers that prefer them
+  --huge-pages <mode>   Huge pages for arrays of at least one huge page (default: off)
+                        transparent: request transparent huge pages with madvise; explicit: map huge pages
+                        reserved in /proc/sys/vm/nr_hugepages, or fall back to transparent huge pages
+  --no-bandwidth        Don't measure the memory bandwidth (STREAM copy and triad) of the machine, which is
+                        otherwise used to report the achieved bandwidth of the algorithms as fraction of the peak
+  --trace               Trace an additional run per algorithm and thread count, and write the events of the
+                        scheduler to a .trace.json file (Chrome trace format, e.g. for ui.perfetto.dev)
+  --perf-counters       Collect hardware performance counters (cycles, instructions, cache and branch misses)
+                        with perf_event_open, and include them in the results
+  --lookback-stats      Count how often the chained scans could scan a block directly, and the length and
+                        waiting iterations of their look-backs
+  --verify              Compare the full output of every algorithm against the sequential reference,
+                        instead of running benchmarks. Uses the given sizes and shapes, or a set of edge cases
+  --scan-file <file>    Scan the array in a file of native-endian 64-bit integers, with the shape given by
+                        --shapes or --sizes, instead of running benchmarks. The file is mapped into memory
+                        and scanned chunk by chunk, such that it may exceed the available memory
+  --output-file <file>  File to write the result of --scan-file to (default: scan the file in place)
+  --calibrate           Calibrate the decision table of the automatic dispatcher, instead of running benchmarks
+  --help                Show this message";
+
+// Options that are followed by a value
+const VALUE_OPTIONS: [&str; 16] = ["--benchmarks", "--sizes", "--shapes", "--algorithms", "--threads", "--runs", "--precision", "--max-runs", "--output-dir", "--config", "--baseline", "--threshold", "--numa", "--huge-pages", "--scan-file", "--output-file"];
+
+pub const DEFAULT_THRESHOLD: f64 = 0.05;
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum CppMode {
+  Ask,
+  Enabled,
+  Disabled
+}
+
+#[derive(Debug)]
+pub struct Options {
+  // None if all benchmarks should be run
+  pub benchmarks: Option<Vec<String>>,
+  pub sizes: Option<Vec<usize>>,
+  pub shapes: Option<Vec<Vec<usize>>>,
+  pub cpp: CppMode,
+  pub calibrate: bool,
+  pub verify: bool,
+  pub perf_counters: bool,
+  pub lookback_statistics: bool,
+  pub help: bool,
+  // Directory of a previous result set to compare against
+  pub baseline: Option<String>,
+  pub threshold: f64,
+  pub compare_only: bool,
+  // Input and optional output file of --scan-file
+  pub scan_file: Option<String>,
+  pub output_file: Option<String>,
+  pub numa: Placement,
+  pub huge_pages: HugePages,
+  pub settings: Settings
+}
+
+impl Options {
+  pub fn includes_benchmark(&self, name: &str) -> bool {
+    match &self.benchmarks {
+      None => true,
+      Some(benchmarks) => benchmarks.iter().any(|benchmark| benchmark == name)
+    }
+  }
+}
+
+pub fn parse<I: Iterator<Item = String>>(args: I, algorithms: &[&str]) -> Result<Options, String> {
+  let mut options = Options{
+    benchmarks: None,
+    sizes: None,
+    shapes: None,
+    cpp: CppMode::Ask,
+    calibrate: false,
+    verify: false,
+    perf_counters: false,
+    lookback_statistics: false,
+    help: false,
+    baseline: None,
+    threshold: DEFAULT_THRESHOLD,
+    compare_only: false,
+    scan_file: None,
+    output_file: None,
+    numa: Placement::FirstTouch,
+    huge_pages: HugePages::Off,
+    settings: Settings::default()
+  };
+  parse_into(&mut options, args, algorithms)?;
+  Ok(options)
+}
+
+fn parse_into<I: Iterator<Item = String>>(options: &mut Options, mut args: I, algorithms: &[&str]) -> Result<(), String> {
+  while let Some(arg) = args.next() {
+    let (flag, inline_value) = match arg.split_once('=') {
+      Some((flag, value)) => (flag.to_owned(), Some(value.to_owned())),
+      None => (arg, None)
+    };
+
+    match flag.as_str() {
+      "--cpp" => options.cpp = CppMode::Enabled,
+      "--no-cpp" => options.cpp = CppMode::Disabled,
+      "--calibrate" => options.calibrate = true,
+      "--verify" => options.verify = true,
+      "--perf-counters" => options.perf_counters = true,
+      "--lookback-stats" => options.lookback_statistics = true,
+      "--no-bandwidth" => options.settings.bandwidth = false,
+      "--trace" => options.settings.trace = true,
+      "--compare-only" => options.compare_only = true,
+      "--help" | "-h" => options.help = true,
+      _ => {
+        if !VALUE_OPTIONS.contains(&flag.as_str()) {
+          return Err(format!("Unknown option '{}'", flag));
+        }
+        let value = match inline_value {
+          Some(value) => value,
+          None => args.next().ok_or(format!("Missing value for {}", flag))?
+        };
+        match flag.as_str() {
+          "--benchmarks" => {
+            let benchmarks = parse_list(&value, |name| {
+              if BENCHMARKS.contains(&name) { Ok(name.to_owned()) } else { Err(format!("Unknown benchmark '{}'", name)) }
+            })?;
+            options.benchmarks = Some(benchmarks);
+          },
+          "--sizes" => options.sizes = Some(parse_list(&value, parse_positive)?),
+          "--shapes" => options.shapes = Some(parse_list(&value, parse_shape)?),
+          "--algorithms" => {
+            let ids: Vec<String> = algorithms.iter().map(|name| algorithm_id(name)).collect();
+            let selected = parse_list(&value, |name| {
+              let id = algorithm_id(name);
+              if ids.contains(&id) { Ok(id) } else { Err(format!("Unknown algorithm '{}', expected one of: {}", name, ids.join(", "))) }
+            })?;
+            options.settings.algorithms = Some(selected);
+          },
+          "--threads" => {
+            let thread_counts = parse_list(&value, parse_positive)?;
+            if let Some(&thread_count) = thread_counts.iter().find(|&&thread_count| thread_count > AFFINITY_MAPPING.len()) {
+              return Err(format!("Thread count {} exceeds the {} cores in AFFINITY_MAPPING", thread_count, AFFINITY_MAPPING.len()));
+            }
+            options.settings.thread_counts = thread_counts;
+          },
+          "--runs" => options.settings.runs = parse_positive(&value)?,
+          "--precision" => {
+            match value.parse::<f64>() {
+              Ok(precision) if precision > 0.0 => options.settings.precision = Some(precision),
+              _ => return Err(format!("Expected a positive fraction, got '{}'", value))
+            }
+          },
+          "--max-runs" => options.settings.max_runs = parse_positive(&value)?,
+          "--baseline" => options.baseline = Some(value),
+          "--threshold" => {
+            match value.parse::<f64>() {
+              Ok(threshold) if threshold >= 0.0 => options.threshold = threshold,
+              _ => return Err(format!("Expected a non-negative fraction, got '{}'", value))
+            }
+          },
+          "--output-dir" => options.settings.output_dir = value,
+          "--numa" => options.numa = Placement::parse(&value)?,
+          "--huge-pages" => options.huge_pages = HugePages::parse(&value)?,
+          "--scan-file" => options.scan_file = Some(value),
+          "--output-file" => options.output_file = Some(value),
+          "--config" => {
+            // Options on the command line after --config override the options in the file.
+            let config_args = read_config(&value)?;
+            parse_into(options, config_args.into_iter(), algorithms)?;
+          },
+          _ => unreachable!()
+        }
+      }
+    }
+  }
+
+  Ok(())
+}
+
+// Converts the lines of a config file to command-line arguments.
+fn read_config(path: &str) -> Result<Vec<String>, String> {
+  let content = std::fs::read_to_string(path).map_err(|e| format!("Cannot read config file {}: {}", path, e))?;
+  let mut args = vec![];
+  for line in content.lines() {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+      continue;
+    }
+    match line.split_once('=') {
+      Some((key, value)) => {
+        args.push("--".to_owned() + key.trim());
+        args.push(value.trim().to_owned());
+      },
+      None => args.push("--".to_owned() + line)
+    }
+  }
+  Ok(args)
+}
+
+fn parse_list<T, F: FnMut(&str) -> Result<T, String>>(value: &str, parse_item: F) -> Result<Vec<T>, String> {
+  let items = value.split(',').map(|item| item.trim()).filter(|item| !item.is_empty()).map(parse_item).collect::<Result<Vec<T>, String>>()?;
+  if items.is_empty() {
+    return Err(format!("Expected a non-empty list, got '{}'", value));
+  }
+  Ok(items)
+}
+
+fn parse_positive(value: &str) -> Result<usize, String> {
+  match value.parse::<usize>() {
+    Ok(number) if number > 0 => Ok(number),
+    _ => Err(format!("Expected a positive number, got '{}'", value))
+  }
+}
+
+fn parse_shape(value: &str) -> Result<Vec<usize>, String> {
+  value.split('x').map(|dimension| parse_positive(dimension.trim())).collect()
+}
@@ -0,0 +1,213 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use crate::utils::benchmark::INDEX_FILE;
+use crate::utils::statistics::t_value;
+
+// Comparison of a benchmark run against a previous result set.
+// Cells (benchmark, algorithm, thread count) are compared on their raw timings from the .csv files
+// using Welch's t-test. For result sets that only contain .dat files (speedups without timings),
+// the speedups are compared instead, without a significance test.
+
+#[derive(Debug, Default)]
+struct Cell {
+  // Time of each run in nanoseconds
+  samples: Vec<f64>,
+  speedup: Option<f64>,
+}
+
+// Key of a cell: benchmark name, algorithm name and thread count
+type ResultSet = BTreeMap<(String, String, usize), Cell>;
+
+// Prints a report of all cells of 'current' that also occur in 'baseline'.
+// Returns the number of cells that are significantly slower than the baseline by more than 'threshold' (a fraction).
+pub fn compare(baseline_dir: &str, current_dir: &str, threshold: f64) -> Result<usize, String> {
+  let baseline = load(baseline_dir)?;
+  let current = load(current_dir)?;
+
+  println!();
+  println!("Comparison of {} against baseline {} (threshold {:.1}%)", current_dir, baseline_dir, threshold * 100.0);
+
+  let mut compared = 0;
+  let mut regressions = 0;
+  let mut improvements = 0;
+  let mut last_benchmark = "";
+  for (key, new) in &current {
+    let Some(old) = baseline.get(key) else { continue; };
+    let Some(comparison) = compare_cell(old, new) else { continue; };
+    compared += 1;
+
+    let (benchmark, algorithm, thread_count) = key;
+    if benchmark != last_benchmark {
+      println!("{}", benchmark);
+      last_benchmark = benchmark;
+    }
+
+    let status = if comparison.change > threshold && comparison.significant {
+      regressions += 1;
+      "REGRESSION"
+    } else if comparison.change < -threshold && comparison.significant {
+      improvements += 1;
+      "improvement"
+    } else {
+      ""
+    };
+    println!("  {:32} {:02} threads {:>+7.2}% {:16} {}", algorithm, thread_count, comparison.change * 100.0, comparison.description, status);
+  }
+
+  println!("Compared {} cells: {} regressions, {} improvements", compared, regressions, improvements);
+  Ok(regressions)
+}
+
+struct Comparison {
+  // Relative slowdown compared to the baseline; positive if the current run is slower
+  change: f64,
+  significant: bool,
+  description: String,
+}
+
+fn compare_cell(old: &Cell, new: &Cell) -> Option<Comparison> {
+  if old.samples.len() >= 2 && new.samples.len() >= 2 {
+    let (old_mean, old_variance) = mean_variance(&old.samples);
+    let (new_mean, new_variance) = mean_variance(&new.samples);
+    let old_n = old.samples.len() as f64;
+    let new_n = new.samples.len() as f64;
+
+    // Welch's t-test, with the Welch-Satterthwaite approximation of the degrees of freedom
+    let old_error = old_variance / old_n;
+    let new_error = new_variance / new_n;
+    let standard_error = (old_error + new_error).sqrt();
+    let significant = if standard_error == 0.0 {
+      old_mean != new_mean
+    } else {
+      let t = (new_mean - old_mean) / standard_error;
+      let degrees_of_freedom = (old_error + new_error).powi(2)
+        / (old_error.powi(2) / (old_n - 1.0) + new_error.powi(2) / (new_n - 1.0));
+      let degrees_of_freedom = if degrees_of_freedom.is_finite() { degrees_of_freedom.floor().max(1.0) as usize } else { 1 };
+      t.abs() > t_value(degrees_of_freedom)
+    };
+    Some(Comparison{
+      change: new_mean / old_mean - 1.0,
+      significant,
+      description: format!("{:.3} -> {:.3} ms", old_mean / 1_000_000.0, new_mean / 1_000_000.0),
+    })
+  } else if let (Some(old_speedup), Some(new_speedup)) = (old.speedup, new.speedup) {
+    // Without timings we cannot test significance; treat every change as significant.
+    Some(Comparison{
+      change: old_speedup / new_speedup - 1.0,
+      significant: true,
+      description: format!("{:.2}x -> {:.2}x", old_speedup, new_speedup),
+    })
+  } else {
+    None
+  }
+}
+
+fn mean_variance(samples: &[f64]) -> (f64, f64) {
+  let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+  let variance = samples.iter().map(|value| (value - mean) * (value - mean)).sum::<f64>() / (samples.len() - 1) as f64;
+  (mean, variance)
+}
+
+// Loads all .csv files of a directory, and the .dat files of benchmarks that have no .csv file.
+// If the directory has an index file, only the benchmarks of the last run are loaded.
+fn load(dir: &str) -> Result<ResultSet, String> {
+  let mut entries: Vec<_> = fs::read_dir(dir).map_err(|e| format!("Cannot read result directory {}: {}", dir, e))?
+    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+    .collect();
+  entries.sort();
+
+  if let Ok(index) = fs::read_to_string(Path::new(dir).join(INDEX_FILE)) {
+    let file_names: Vec<&str> = index.lines()
+      .filter(|line| !line.starts_with('#'))
+      .filter_map(|line| line.split('\t').next())
+      .collect();
+    entries.retain(|path| path.file_stem().and_then(|stem| stem.to_str()).is_some_and(|stem| file_names.contains(&stem)));
+  }
+
+  let mut results = ResultSet::new();
+  for path in entries.iter().filter(|path| has_extension(path, "csv")) {
+    load_csv(path, &mut results)?;
+  }
+  for path in entries.iter().filter(|path| has_extension(path, "dat")) {
+    load_dat(path, &mut results)?;
+  }
+  Ok(results)
+}
+
+fn has_extension(path: &Path, extension: &str) -> bool {
+  path.extension().is_some_and(|e| e == extension)
+}
+
+fn load_csv(path: &Path, results: &mut ResultSet) -> Result<(), String> {
+  let content = fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+  let mut lines = content.lines();
+  let header = split_csv_line(lines.next().unwrap_or(""));
+  let column = |name: &str| header.iter().position(|h| h == name).ok_or(format!("Missing column {} in {}", name, path.display()));
+  let (benchmark_col, algorithm_col, threads_col, time_col, speedup_col) =
+    (column("benchmark")?, column("algorithm")?, column("threads")?, column("time_ns")?, column("speedup")?);
+
+  for line in lines {
+    let fields = split_csv_line(line);
+    if fields.len() < header.len() {
+      continue;
+    }
+    let thread_count = fields[threads_col].parse().map_err(|_| format!("Invalid thread count in {}", path.display()))?;
+    let cell = results.entry((fields[benchmark_col].clone(), fields[algorithm_col].clone(), thread_count)).or_default();
+    if let Ok(time) = fields[time_col].parse::<f64>() {
+      cell.samples.push(time);
+    }
+    if let Ok(speedup) = fields[speedup_col].parse::<f64>() {
+      cell.speedup = Some(speedup);
+    }
+  }
+  Ok(())
+}
+
+// Parses a .dat file as written by Benchmarker, containing the speedup per thread count and algorithm.
+fn load_dat(path: &Path, results: &mut ResultSet) -> Result<(), String> {
+  let content = fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+  let Some(benchmark) = content.lines().next().and_then(|line| line.strip_prefix("# Benchmark ")) else {
+    return Ok(());
+  };
+  if results.keys().any(|(name, _, _)| name == benchmark) {
+    // Already loaded from the .csv file, which contains more information.
+    return Ok(());
+  }
+  let Some(header) = content.lines().find(|line| line.starts_with("# NCPU")) else {
+    return Ok(());
+  };
+  let algorithms: Vec<&str> = header.split('\t').skip(1).collect();
+
+  for line in content.lines().filter(|line| !line.starts_with('#')) {
+    let mut fields = line.split('\t');
+    let Some(Ok(thread_count)) = fields.next().map(|field| field.parse::<usize>()) else { continue; };
+    for (algorithm, field) in algorithms.iter().zip(fields) {
+      if let Ok(speedup) = field.parse::<f64>() {
+        let cell = results.entry((benchmark.to_owned(), algorithm.to_string(), thread_count)).or_default();
+        cell.speedup = Some(speedup);
+      }
+    }
+  }
+  Ok(())
+}
+
+fn split_csv_line(line: &str) -> Vec<String> {
+  let mut fields = vec![];
+  let mut field = String::new();
+  let mut quoted = false;
+  let mut chars = line.chars().peekable();
+  while let Some(c) = chars.next() {
+    match c {
+      '"' if quoted && chars.peek() == Some(&'"') => {
+        field.push('"');
+        chars.next();
+      },
+      '"' => quoted = !quoted,
+      ',' if !quoted => fields.push(std::mem::take(&mut field)),
+      c => field.push(c)
+    }
+  }
+  fields.push(field);
+  fields
+}
@@ -0,0 +1,125 @@
+use core::sync::atomic::{AtomicU8, Ordering};
+use std::sync::OnceLock;
+
+// Huge pages for large arrays, to reduce the number of TLB misses when scanning multiple gigabytes.
+// Arrays of at least one huge page are mapped separately, aligned to the huge page size, instead of
+// being allocated on the heap. Smaller arrays would waste most of their huge page, and stay on the heap.
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum HugePages {
+  // Allocate on the heap, with the default policy of the kernel for transparent huge pages
+  Off,
+  // Map anonymous memory and request transparent huge pages with madvise(MADV_HUGEPAGE)
+  Transparent,
+  // Map explicit huge pages of hugetlbfs (MAP_HUGETLB), which must be reserved in /proc/sys/vm/nr_hugepages.
+  // Falls back to transparent huge pages if not enough huge pages are available.
+  Explicit
+}
+
+impl HugePages {
+  pub fn parse(value: &str) -> Result<HugePages, String> {
+    match value {
+      "off" => Ok(HugePages::Off),
+      "transparent" => Ok(HugePages::Transparent),
+      "explicit" => Ok(HugePages::Explicit),
+      _ => Err(format!("Unknown huge page mode '{}', expected one of: off, transparent, explicit", value))
+    }
+  }
+
+  pub fn name(self) -> &'static str {
+    match self {
+      HugePages::Off => "off",
+      HugePages::Transparent => "transparent",
+      HugePages::Explicit => "explicit"
+    }
+  }
+}
+
+static MODE: AtomicU8 = AtomicU8::new(0);
+
+pub fn set_mode(mode: HugePages) {
+  MODE.store(mode as u8, Ordering::Relaxed);
+}
+
+pub fn mode() -> HugePages {
+  match MODE.load(Ordering::Relaxed) {
+    1 => HugePages::Transparent,
+    2 => HugePages::Explicit,
+    _ => HugePages::Off
+  }
+}
+
+// Size of a huge page in bytes, as reported by the kernel, or 2 MB if unknown
+pub fn huge_page_size() -> usize {
+  static SIZE: OnceLock<usize> = OnceLock::new();
+  *SIZE.get_or_init(|| {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").unwrap_or_default();
+    meminfo.lines()
+      .find_map(|line| line.strip_prefix("Hugepagesize:"))
+      .and_then(|size| size.trim().strip_suffix("kB")?.trim().parse::<usize>().ok())
+      .map(|kilobytes| kilobytes * 1024)
+      .unwrap_or(2 * 1024 * 1024)
+  })
+}
+
+// A mapping of memory for a large array
+pub struct Mapping {
+  pub address: *mut u8,
+  pub size: usize
+}
+
+// Maps at least 'bytes' bytes, aligned to the huge page size. Returns None if huge pages are disabled
+// or the array is smaller than a huge page, in which case it should be allocated on the heap.
+pub fn map(bytes: usize) -> Option<Mapping> {
+  let page = huge_page_size();
+  let mode = mode();
+  if mode == HugePages::Off || bytes < page {
+    return None;
+  }
+  let size = bytes.div_ceil(page) * page;
+
+  if mode == HugePages::Explicit {
+    if let Some(address) = mmap(size, libc::MAP_HUGETLB) {
+      return Some(Mapping{ address, size });
+    }
+    warn_fallback();
+  }
+
+  // Transparent huge pages can only be used for the aligned parts of a mapping. Hence we map an additional
+  // huge page, and unmap the unaligned parts before and after the array.
+  let address = mmap(size + page, 0)?;
+  let start = (address as usize).div_ceil(page) * page;
+  let before = start - address as usize;
+  let after = page - before;
+  unsafe {
+    if before != 0 {
+      libc::munmap(address as *mut libc::c_void, before);
+    }
+    if after != 0 {
+      libc::munmap((start + size) as *mut libc::c_void, after);
+    }
+    // Only a hint, hence failures (for instance when transparent huge pages are disabled) are ignored
+    libc::madvise(start as *mut libc::c_void, size, libc::MADV_HUGEPAGE);
+  }
+  Some(Mapping{ address: start as *mut u8, size })
+}
+
+pub fn unmap(mapping: &Mapping) {
+  unsafe {
+    libc::munmap(mapping.address as *mut libc::c_void, mapping.size);
+  }
+}
+
+fn mmap(size: usize, flags: libc::c_int) -> Option<*mut u8> {
+  let address = unsafe {
+    libc::mmap(std::ptr::null_mut(), size, libc::PROT_READ | libc::PROT_WRITE, libc::MAP_PRIVATE | libc::MAP_ANONYMOUS | flags, -1, 0)
+  };
+  if address == libc::MAP_FAILED { None } else { Some(address as *mut u8) }
+}
+
+fn warn_fallback() {
+  static WARNED: OnceLock<()> = OnceLock::new();
+  WARNED.get_or_init(|| {
+    eprintln!("Not enough explicit huge pages available (see /proc/sys/vm/nr_hugepages), falling back to transparent huge pages.");
+  });
+}
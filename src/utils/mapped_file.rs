@@ -0,0 +1,66 @@
+use std::fs::OpenOptions;
+use std::os::unix::io::AsRawFd;
+use crate::utils::huge_pages::Mapping;
+
+// Arrays stored in a file, as native-endian elements in row-major order, which are mapped into memory
+// instead of being loaded. The kernel reads pages when they are first accessed, and can evict pages of
+// the input (and write back pages of the output) when memory is needed, such that the array may exceed
+// the available memory.
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Access {
+  // Maps an existing file read-only. Writing to the array crashes the process, hence it can only be used as input.
+  ReadOnly,
+  // Maps an existing file writable, for instance to scan it in place
+  ReadWrite,
+  // Creates the file, or truncates an existing file, to the size of the array and maps it writable, for an output.
+  // The array initially contains zeroes.
+  Create
+}
+
+// Maps 'bytes' bytes of the file at 'path'. Returns None for an empty array, as mmap cannot map zero bytes.
+// Existing files must have exactly the size of the array.
+pub fn map(path: &str, bytes: usize, access: Access) -> Result<Option<Mapping>, String> {
+  let file = OpenOptions::new()
+    .read(true)
+    .write(access != Access::ReadOnly)
+    .create(access == Access::Create)
+    .truncate(access == Access::Create)
+    .open(path)
+    .map_err(|error| format!("Cannot open {}: {}", path, error))?;
+
+  if access == Access::Create {
+    file.set_len(bytes as u64).map_err(|error| format!("Cannot resize {}: {}", path, error))?;
+  } else {
+    let length = file.metadata().map_err(|error| format!("Cannot read the size of {}: {}", path, error))?.len();
+    if length != bytes as u64 {
+      return Err(format!("{} contains {} bytes, but the shape requires {} bytes", path, length, bytes));
+    }
+  }
+  if bytes == 0 {
+    return Ok(None);
+  }
+
+  let protection = if access == Access::ReadOnly { libc::PROT_READ } else { libc::PROT_READ | libc::PROT_WRITE };
+  // The mapping stays valid after the file is closed
+  let address = unsafe {
+    libc::mmap(std::ptr::null_mut(), bytes, protection, libc::MAP_SHARED, file.as_raw_fd(), 0)
+  };
+  if address == libc::MAP_FAILED {
+    return Err(format!("Cannot map {}: {}", path, std::io::Error::last_os_error()));
+  }
+  // The scans access the array mostly in order, chunk by chunk. Only a hint, hence failures are ignored.
+  unsafe {
+    libc::madvise(address, bytes, libc::MADV_SEQUENTIAL);
+  }
+  Ok(Some(Mapping{ address: address as *mut u8, size: bytes }))
+}
+
+// Writes the modified pages of a mapping back to its file
+pub fn sync(mapping: &Mapping) -> Result<(), String> {
+  let result = unsafe { libc::msync(mapping.address as *mut libc::c_void, mapping.size, libc::MS_SYNC) };
+  if result != 0 {
+    return Err(format!("Cannot write back the mapped file: {}", std::io::Error::last_os_error()));
+  }
+  Ok(())
+}
@@ -0,0 +1,64 @@
+use std::sync::OnceLock;
+use crate::cases::scan::BLOCK_SIZE;
+
+// Information about the machine and the build, stored alongside the benchmark results.
+#[derive(Debug)]
+pub struct Metadata {
+  pub host: String,
+  pub cpu_model: String,
+  pub cpu_count: usize,
+  pub os: String,
+  pub git_revision: String,
+  pub git_dirty: bool,
+  pub build_profile: &'static str,
+  pub block_size: u64,
+  // Seconds since the Unix epoch, at the start of the benchmark run
+  pub timestamp: u64,
+}
+
+static METADATA: OnceLock<Metadata> = OnceLock::new();
+
+// Collects the metadata on first use. Later calls return the same values.
+pub fn get() -> &'static Metadata {
+  METADATA.get_or_init(collect)
+}
+
+fn collect() -> Metadata {
+  let git_revision = command_output("git", &["rev-parse", "HEAD"]).unwrap_or_else(|| "unknown".to_owned());
+  let git_dirty = command_output("git", &["status", "--porcelain", "--untracked-files=no"]).is_some_and(|status| !status.is_empty());
+
+  Metadata {
+    host: read_trimmed("/proc/sys/kernel/hostname").or_else(|| std::env::var("HOSTNAME").ok()).unwrap_or_else(|| "unknown".to_owned()),
+    cpu_model: cpu_model().unwrap_or_else(|| "unknown".to_owned()),
+    cpu_count: std::thread::available_parallelism().map(|count| count.get()).unwrap_or(0),
+    os: match read_trimmed("/proc/sys/kernel/osrelease") {
+      Some(release) => std::env::consts::OS.to_owned() + " " + &release,
+      None => std::env::consts::OS.to_owned()
+    },
+    git_revision,
+    git_dirty,
+    build_profile: if cfg!(debug_assertions) { "debug" } else { "release" },
+    block_size: BLOCK_SIZE,
+    timestamp: std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0),
+  }
+}
+
+fn cpu_model() -> Option<String> {
+  let cpuinfo = std::fs::read_to_string("/proc/cpuinfo").ok()?;
+  cpuinfo.lines()
+    .find(|line| line.starts_with("model name"))
+    .and_then(|line| line.split_once(':'))
+    .map(|(_, model)| model.trim().to_owned())
+}
+
+fn read_trimmed(path: &str) -> Option<String> {
+  std::fs::read_to_string(path).ok().map(|content| content.trim().to_owned())
+}
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+  let output = std::process::Command::new(program).args(args).output().ok()?;
+  if !output.status.success() {
+    return None;
+  }
+  Some(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
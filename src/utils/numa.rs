@@ -0,0 +1,214 @@
+use core::sync::atomic::{AtomicU8, AtomicU32, Ordering};
+use std::sync::OnceLock;
+
+// NUMA awareness of the benchmarks: the topology of the machine, the placement of the pages of large arrays,
+// and the preference of workers for blocks that are resident on their own node.
+// With the 'local' and 'first-touch' placement, the pages of an array are divided in one contiguous part per node,
+// such that blocks with a low index are on the first node and blocks with a high index on the last node.
+// NodeClaims divides the blocks of a task in the same way.
+
+// Placement of the pages of large arrays
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Placement {
+  // Pages are placed on the node of the thread that first writes them. The inputs are filled by the workers
+  // with a NUMA-aware loop, which gives the same layout as 'local'.
+  FirstTouch,
+  // Pages are distributed round-robin over all nodes
+  Interleave,
+  // Pages are bound to the node of their part of the array when the array is allocated. They do not fall back
+  // to other nodes, hence every part must fit in the memory of its node.
+  Local
+}
+
+impl Placement {
+  pub fn parse(value: &str) -> Result<Placement, String> {
+    match value {
+      "first-touch" => Ok(Placement::FirstTouch),
+      "interleave" => Ok(Placement::Interleave),
+      "local" => Ok(Placement::Local),
+      _ => Err(format!("Unknown NUMA placement '{}', expected one of: first-touch, interleave, local", value))
+    }
+  }
+}
+
+static PLACEMENT: AtomicU8 = AtomicU8::new(0);
+
+pub fn set_placement(placement: Placement) {
+  PLACEMENT.store(placement as u8, Ordering::Relaxed);
+}
+
+pub fn placement() -> Placement {
+  match PLACEMENT.load(Ordering::Relaxed) {
+    1 => Placement::Interleave,
+    2 => Placement::Local,
+    _ => Placement::FirstTouch
+  }
+}
+
+pub struct Topology {
+  // Node of every CPU, indexed by the CPU number. Nodes are numbered 0 .. node_count.
+  cpu_nodes: Vec<usize>,
+  // Id of every node in the kernel, which may not be consecutive
+  node_ids: Vec<usize>,
+  node_count: usize
+}
+
+static TOPOLOGY: OnceLock<Topology> = OnceLock::new();
+
+// Reads the topology on first use. Machines without NUMA information are treated as a single node.
+pub fn topology() -> &'static Topology {
+  TOPOLOGY.get_or_init(detect)
+}
+
+impl Topology {
+  pub fn node_count(&self) -> usize {
+    self.node_count
+  }
+
+  pub fn node_of_cpu(&self, cpu: usize) -> usize {
+    self.cpu_nodes.get(cpu).copied().unwrap_or(0)
+  }
+}
+
+fn detect() -> Topology {
+  let mut cpu_nodes = vec![];
+  let mut node_count = 0;
+  let mut online_ids = vec![];
+  // Nodes may not be numbered consecutively, hence we number them in the order of their ids
+  let mut node_ids: Vec<usize> = std::fs::read_dir("/sys/devices/system/node").into_iter().flatten()
+    .filter_map(|entry| entry.ok()?.file_name().to_str()?.strip_prefix("node")?.parse().ok())
+    .collect();
+  node_ids.sort_unstable();
+
+  for id in node_ids {
+    let Ok(cpulist) = std::fs::read_to_string(format!("/sys/devices/system/node/node{}/cpulist", id)) else { continue; };
+    for cpu in parse_cpulist(cpulist.trim()) {
+      if cpu >= cpu_nodes.len() {
+        cpu_nodes.resize(cpu + 1, 0);
+      }
+      cpu_nodes[cpu] = node_count;
+    }
+    online_ids.push(id);
+    node_count += 1;
+  }
+  if online_ids.is_empty() {
+    online_ids.push(0);
+  }
+  Topology{ cpu_nodes, node_count: online_ids.len(), node_ids: online_ids }
+}
+
+// Parses a list of CPUs like '0-7,16-23'
+fn parse_cpulist(list: &str) -> Vec<usize> {
+  let mut cpus = vec![];
+  for range in list.split(',').filter(|range| !range.is_empty()) {
+    match range.split_once('-') {
+      Some((first, last)) => {
+        if let (Ok(first), Ok(last)) = (first.parse::<usize>(), last.parse::<usize>()) {
+          cpus.extend(first ..= last);
+        }
+      },
+      None => cpus.extend(range.parse::<usize>().ok())
+    }
+  }
+  cpus
+}
+
+// Node of the CPU on which the calling thread runs. The workers are pinned, so this doesn't change.
+pub fn current_node() -> usize {
+  let cpu = unsafe { libc::sched_getcpu() };
+  if cpu < 0 { 0 } else { topology().node_of_cpu(cpu as usize) }
+}
+
+// Number of parts in which blocks are divided over the nodes, or 1 if workers have no preference
+pub fn preferred_parts() -> usize {
+  if placement() == Placement::Interleave { 1 } else { topology().node_count() }
+}
+
+// Applies the placement to the pages of a freshly allocated array, before they are touched.
+// Pages that were already touched, for instance of small allocations on the heap, keep their node.
+pub fn place<T>(data: &[T]) {
+  let topology = topology();
+  let node_count = topology.node_count();
+  if node_count == 1 {
+    return;
+  }
+  let start = data.as_ptr() as usize;
+  let length = std::mem::size_of_val(data);
+  match placement() {
+    Placement::FirstTouch => {},
+    Placement::Interleave => {
+      let mask = node_mask(&topology.node_ids);
+      bind(start, start + length, libc::MPOL_INTERLEAVE, &mask);
+    },
+    Placement::Local => {
+      for node in 0 .. node_count {
+        let mask = node_mask(&topology.node_ids[node ..= node]);
+        bind(start + length * node / node_count, start + length * (node + 1) / node_count, libc::MPOL_BIND, &mask);
+      }
+    }
+  }
+}
+
+fn node_mask(node_ids: &[usize]) -> Vec<libc::c_ulong> {
+  let bits = libc::c_ulong::BITS as usize;
+  let mut mask = vec![0; (node_ids.iter().max().unwrap_or(&0) + 1).div_ceil(bits)];
+  for &id in node_ids {
+    mask[id / bits] |= 1 << (id % bits);
+  }
+  mask
+}
+
+// Sets the memory policy of the whole pages within start .. end. Failures are ignored,
+// as the placement is only a performance hint.
+fn bind(start: usize, end: usize, mode: libc::c_int, mask: &[libc::c_ulong]) {
+  let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+  let start = start.div_ceil(page_size) * page_size;
+  let end = end / page_size * page_size;
+  if end <= start {
+    return;
+  }
+  let max_node = mask.len() * libc::c_ulong::BITS as usize;
+  unsafe {
+    libc::syscall(libc::SYS_mbind, start as *mut libc::c_void, end - start, mode, mask.as_ptr(), max_node, 0);
+  }
+}
+
+// Per-node counters to claim the blocks of a data-parallel task. The blocks are divided in one contiguous
+// range per node, and a worker first claims blocks from the range of its own node. When that range is
+// exhausted, it assists the other nodes. Only suitable for tasks of which the blocks are independent,
+// as blocks are not claimed in order.
+pub struct NodeClaims {
+  starts: Box<[u32]>,
+  next: Box<[AtomicU32]>
+}
+
+impl NodeClaims {
+  pub fn new(work_size: u32) -> NodeClaims {
+    let parts = preferred_parts() as u64;
+    let starts: Box<[u32]> = (0 ..= parts).map(|part| (work_size as u64 * part / parts) as u32).collect();
+    let next = starts[.. parts as usize].iter().map(|&start| AtomicU32::new(start)).collect();
+    NodeClaims{ starts, next }
+  }
+
+  // Resets the counters, such that the blocks can be claimed again by the next phase of a task
+  pub fn reset(&self) {
+    for (next, &start) in self.next.iter().zip(self.starts.iter()) {
+      next.store(start, Ordering::Relaxed);
+    }
+  }
+
+  // Claims a block, preferably of the given node. Every caller should hold one of the work_size tickets of the
+  // task, such that a block is always available: a range that is exhausted stays exhausted, hence a single
+  // pass over all ranges finds a block.
+  pub fn claim(&self, node: usize) -> u32 {
+    let parts = self.next.len();
+    for offset in 0 .. parts {
+      let part = (node + offset) % parts;
+      let block = self.next[part].fetch_add(1, Ordering::Relaxed);
+      if block < self.starts[part + 1] {
+        return block;
+      }
+    }
+    panic!("Claimed more blocks than available");
+  }
+}
@@ -0,0 +1,210 @@
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Optional hardware performance counters, using perf_event_open on Linux.
+// Counters are collected per thread: around the timed region on the benchmarking thread
+// and inside Workers::run on every worker thread. The counts of all threads are summed.
+// Workers only count during a timed run (between start_run and finish_run), such that the preparation of the
+// inputs, which may also use the workers, is not included.
+// Only user space is counted, such that this works with perf_event_paranoid up to 2.
+
+// Names and configs (PERF_TYPE_HARDWARE) of the counted events
+pub const EVENTS: [(&str, u64); 5] = [
+  ("cycles", 0),
+  ("instructions", 1),
+  ("cache-references", 2),
+  ("cache-misses", 3),
+  ("branch-misses", 5),
+];
+const EVENT_COUNT: usize = EVENTS.len();
+pub const CACHE_LINE_SIZE: u64 = 64;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Counters {
+  // Per event, None if the event is not supported on this machine
+  pub values: [Option<u64>; EVENT_COUNT],
+}
+
+impl Counters {
+  pub fn get(&self, name: &str) -> Option<u64> {
+    EVENTS.iter().position(|(event, _)| *event == name).and_then(|idx| self.values[idx])
+  }
+
+  fn add(&mut self, other: &Counters) {
+    for idx in 0 .. EVENT_COUNT {
+      if let Some(value) = other.values[idx] {
+        self.values[idx] = Some(self.values[idx].unwrap_or(0) + value);
+      }
+    }
+  }
+
+  pub fn divide(&self, divisor: u64) -> Counters {
+    Counters{ values: self.values.map(|value| value.map(|value| value / divisor.max(1))) }
+  }
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static UNAVAILABLE: AtomicBool = AtomicBool::new(false);
+// Whether a timed run is in progress
+static MEASURING: AtomicBool = AtomicBool::new(false);
+// Sum of the counters of all threads since the last reset, and the number of timed runs
+static TOTAL: Mutex<(Counters, u64)> = Mutex::new((Counters{ values: [None; EVENT_COUNT] }, 0));
+
+pub fn set_enabled(enabled: bool) {
+  ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+  ENABLED.load(Ordering::Relaxed)
+}
+
+pub fn reset() {
+  *TOTAL.lock().unwrap() = (Counters::default(), 0);
+}
+
+// Starts a timed run, and the counters of the calling thread
+pub fn start_run() -> Option<ThreadCounters> {
+  MEASURING.store(true, Ordering::Relaxed);
+  ThreadCounters::start()
+}
+
+// Stops the counters of the calling thread, and registers that the timed run has been completed
+pub fn finish_run(counters: Option<ThreadCounters>) {
+  stop(counters);
+  MEASURING.store(false, Ordering::Relaxed);
+  if is_enabled() {
+    TOTAL.lock().unwrap().1 += 1;
+  }
+}
+
+// Returns the average counters per timed run since the last reset,
+// or None if counters are disabled or not available.
+pub fn take_per_run() -> Option<Counters> {
+  if !is_enabled() {
+    return None;
+  }
+  let (counters, runs) = std::mem::take(&mut *TOTAL.lock().unwrap());
+  if runs == 0 || counters.values.iter().all(|value| value.is_none()) {
+    return None;
+  }
+  Some(counters.divide(runs))
+}
+
+// Counters of the current thread, between start and stop
+pub struct ThreadCounters {
+  // File descriptors of the opened events. The first one is the group leader.
+  fds: Vec<(usize, i32)>,
+}
+
+impl ThreadCounters {
+  // Opens and enables the counters of the current thread. Returns None if counters are disabled or not available,
+  // or if no timed run is in progress.
+  pub fn start() -> Option<ThreadCounters> {
+    if !is_enabled() || !MEASURING.load(Ordering::Relaxed) || UNAVAILABLE.load(Ordering::Relaxed) {
+      return None;
+    }
+    let mut fds: Vec<(usize, i32)> = vec![];
+    for (idx, &(_, config)) in EVENTS.iter().enumerate() {
+      let group_fd = fds.first().map_or(-1, |&(_, fd)| fd);
+      let fd = open_event(config, group_fd);
+      if fd >= 0 {
+        fds.push((idx, fd));
+      }
+    }
+    if fds.is_empty() {
+      if !UNAVAILABLE.swap(true, Ordering::Relaxed) {
+        println!("Performance counters are not available (check /proc/sys/kernel/perf_event_paranoid).");
+      }
+      return None;
+    }
+    let leader = fds[0].1;
+    unsafe {
+      libc::ioctl(leader, PERF_EVENT_IOC_RESET, PERF_IOC_FLAG_GROUP);
+      libc::ioctl(leader, PERF_EVENT_IOC_ENABLE, PERF_IOC_FLAG_GROUP);
+    }
+    Some(ThreadCounters{ fds })
+  }
+
+  // Disables the counters and adds them to the total
+  pub fn stop(self) {
+    let leader = self.fds[0].1;
+    unsafe { libc::ioctl(leader, PERF_EVENT_IOC_DISABLE, PERF_IOC_FLAG_GROUP); }
+
+    // Layout with PERF_FORMAT_GROUP: number of events, time enabled, time running, and a value per event
+    let mut buffer = [0u64; 3 + EVENT_COUNT];
+    let size = unsafe { libc::read(leader, buffer.as_mut_ptr() as *mut libc::c_void, std::mem::size_of_val(&buffer)) };
+    if size > 0 {
+      let (time_enabled, time_running) = (buffer[1], buffer[2]);
+      let mut counters = Counters::default();
+      for (position, &(idx, _)) in self.fds.iter().enumerate().take(buffer[0] as usize) {
+        // Scale the value if the counters were multiplexed with other events
+        let value = if time_running == 0 { 0 } else { (buffer[3 + position] as u128 * time_enabled as u128 / time_running as u128) as u64 };
+        counters.values[idx] = Some(value);
+      }
+      TOTAL.lock().unwrap().0.add(&counters);
+    }
+
+    for &(_, fd) in &self.fds {
+      unsafe { libc::close(fd); }
+    }
+  }
+}
+
+// Stops the counters, if they were started
+pub fn stop(counters: Option<ThreadCounters>) {
+  if let Some(counters) = counters {
+    counters.stop();
+  }
+}
+
+const PERF_TYPE_HARDWARE: u32 = 0;
+const PERF_FORMAT_TOTAL_TIME_ENABLED: u64 = 1 << 0;
+const PERF_FORMAT_TOTAL_TIME_RUNNING: u64 = 1 << 1;
+const PERF_FORMAT_GROUP: u64 = 1 << 3;
+const ATTR_FLAG_DISABLED: u64 = 1 << 0;
+const ATTR_FLAG_EXCLUDE_KERNEL: u64 = 1 << 5;
+const ATTR_FLAG_EXCLUDE_HV: u64 = 1 << 6;
+const PERF_EVENT_IOC_ENABLE: libc::c_ulong = 0x2400;
+const PERF_EVENT_IOC_DISABLE: libc::c_ulong = 0x2401;
+const PERF_EVENT_IOC_RESET: libc::c_ulong = 0x2403;
+const PERF_IOC_FLAG_GROUP: libc::c_ulong = 1;
+
+// struct perf_event_attr (PERF_ATTR_SIZE_VER5)
+#[repr(C)]
+#[derive(Default)]
+struct PerfEventAttr {
+  type_: u32,
+  size: u32,
+  config: u64,
+  sample_period: u64,
+  sample_type: u64,
+  read_format: u64,
+  flags: u64,
+  wakeup_events: u32,
+  bp_type: u32,
+  config1: u64,
+  config2: u64,
+  branch_sample_type: u64,
+  sample_regs_user: u64,
+  sample_stack_user: u32,
+  clockid: i32,
+  sample_regs_intr: u64,
+  aux_watermark: u32,
+  sample_max_stack: u16,
+  reserved: u16,
+}
+
+// Opens a counter on the current thread, on any CPU. Returns a negative value on failure.
+fn open_event(config: u64, group_fd: i32) -> i32 {
+  let is_leader = group_fd == -1;
+  let attr = PerfEventAttr{
+    type_: PERF_TYPE_HARDWARE,
+    size: std::mem::size_of::<PerfEventAttr>() as u32,
+    config,
+    read_format: PERF_FORMAT_GROUP | PERF_FORMAT_TOTAL_TIME_ENABLED | PERF_FORMAT_TOTAL_TIME_RUNNING,
+    // Only the group leader starts disabled; the other events follow the leader.
+    flags: (if is_leader { ATTR_FLAG_DISABLED } else { 0 }) | ATTR_FLAG_EXCLUDE_KERNEL | ATTR_FLAG_EXCLUDE_HV,
+    ..Default::default()
+  };
+  unsafe { libc::syscall(libc::SYS_perf_event_open, &attr as *const PerfEventAttr, 0, -1, group_fd, 0) as i32 }
+}
@@ -0,0 +1,85 @@
+// Summary statistics of the timings of a benchmark.
+// All values are in the unit of the samples (nanoseconds in the benchmarks).
+
+#[derive(Debug, Clone)]
+pub struct Statistics {
+  pub count: usize,
+  pub mean: f64,
+  pub min: f64,
+  pub max: f64,
+  pub median: f64,
+  pub p90: f64,
+  pub p99: f64,
+  pub std_dev: f64,
+  // Bounds of the 95% confidence interval of the mean
+  pub ci_low: f64,
+  pub ci_high: f64,
+  // Number of samples outside the Tukey fences (1.5 times the interquartile range below Q1 or above Q3)
+  pub outliers: usize,
+}
+
+impl Statistics {
+  pub fn new(samples: &[u64]) -> Statistics {
+    assert!(!samples.is_empty(), "Cannot compute statistics without samples");
+    let mut sorted: Vec<f64> = samples.iter().map(|&sample| sample as f64).collect();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    let count = sorted.len();
+    let mean = sorted.iter().sum::<f64>() / count as f64;
+    let variance = if count > 1 {
+      sorted.iter().map(|value| (value - mean) * (value - mean)).sum::<f64>() / (count - 1) as f64
+    } else {
+      0.0
+    };
+    let std_dev = variance.sqrt();
+    let half_width = if count > 1 { t_value(count - 1) * std_dev / (count as f64).sqrt() } else { f64::INFINITY };
+
+    let q1 = percentile(&sorted, 25.0);
+    let q3 = percentile(&sorted, 75.0);
+    let iqr = q3 - q1;
+    let outliers = sorted.iter().filter(|&&value| value < q1 - 1.5 * iqr || value > q3 + 1.5 * iqr).count();
+
+    Statistics {
+      count,
+      mean,
+      min: sorted[0],
+      max: sorted[count - 1],
+      median: percentile(&sorted, 50.0),
+      p90: percentile(&sorted, 90.0),
+      p99: percentile(&sorted, 99.0),
+      std_dev,
+      ci_low: mean - half_width,
+      ci_high: mean + half_width,
+      outliers,
+    }
+  }
+
+  // Half-width of the confidence interval, relative to the mean
+  pub fn relative_ci(&self) -> f64 {
+    (self.ci_high - self.ci_low) / 2.0 / self.mean
+  }
+}
+
+// Percentile of sorted values, with linear interpolation between the closest ranks
+fn percentile(sorted: &[f64], percentage: f64) -> f64 {
+  let rank = percentage / 100.0 * (sorted.len() - 1) as f64;
+  let lower = rank.floor() as usize;
+  let upper = rank.ceil() as usize;
+  sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+}
+
+// Two-sided critical value of Student's t-distribution for a 95% confidence level
+pub fn t_value(degrees_of_freedom: usize) -> f64 {
+  const TABLE: [f64; 30] = [
+    12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228,
+    2.201, 2.179, 2.160, 2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086,
+    2.080, 2.074, 2.069, 2.064, 2.060, 2.056, 2.052, 2.048, 2.045, 2.042
+  ];
+  match degrees_of_freedom {
+    0 => f64::INFINITY,
+    1 ..= 30 => TABLE[degrees_of_freedom - 1],
+    31 ..= 60 => 2.000,
+    61 ..= 120 => 1.980,
+    _ => 1.960
+  }
+}